@@ -0,0 +1,429 @@
+use serde_json::Value;
+
+use super::{
+    bookmarks::{Bookmark, Bookmarks},
+    clock::Clock,
+    coloring::Coloring,
+    event_handler::active_area,
+    export::{encode_png, png_text},
+    link,
+    migration::migrate,
+    palette::html_to_rgba,
+    render::{create_fractal, render_image},
+    util::height_for_ratio,
+    validation, Config, Renderer, BACKGROUND_COLOR, DEFAULT_WIDTH,
+};
+
+// edge length limit, the buffers of the largest image take about 1 GB
+const MAX_SIZE: u32 = 8192;
+
+pub const USAGE: &str = "\
+Usage: seed-fractals-cli [OPTIONS] --output FILE
+
+Renders a fractal to a PNG or PPM file, the format is chosen by the file extension.
+The view starts from the defaults of the web app, a saved config or bookmark, and is
+changed by the other options like a single deep link, a repeated option overrides the
+earlier ones.
+
+Options:
+  --output FILE        image file to write, *.png or *.ppm
+  --width PIXELS       image width, default 1024
+  --height PIXELS      image height, default keeps the aspect ratio of the area
+  --config FILE        config JSON, a bookmark or an exported bookmark library
+  --bookmark NAME      bookmark to use from a library, default the first one
+  --link HASH          deep link of the web app, e.g. '#type=julia&c=-0.8,0.156'
+  --type TYPE          mandelbrot, julia, burning_ship or newton
+  --min RE,IM          lower left corner of the area
+  --max RE,IM          upper right corner of the area
+  --iter N             maximum iterations
+  --exp X              exponent, Mandelbrot and Julia sets
+  --c RE,IM            constant of the Julia set
+  --coef A,B,..        polynomial coefficients of the Newton fractal, highest power first
+  --palette SPEC       color stops, e.g. 'hsl;0:FF0000;1:FF00FF'
+  --renderer NAME      scanline or subdivision
+  --smooth on|off      smooth coloring
+  --help               show this text
+";
+
+/// Output file formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Png,
+    // binary portable pixmap, without the alpha channel
+    Ppm,
+}
+
+/// A render requested on the command line.
+pub struct Job {
+    config: Config,
+    width: u32,
+    height: u32,
+    output: String,
+    format: Format,
+}
+
+// option names which are also deep link parameters, see link::apply
+const LINK_OPTIONS: [&str; 8] = ["type", "min", "max", "iter", "exp", "c", "coef", "palette"];
+
+impl Job {
+    /// Parse the command line `args` without the program name. `read_file` returns the contents
+    /// of the file given with `--config`. Returns `Ok(None)` if help was requested.
+    ///
+    /// # Errors
+    /// Describes the first invalid or missing option.
+    pub fn parse(
+        args: &[String],
+        read_file: impl Fn(&str) -> Result<String, String>,
+    ) -> Result<Option<Self>, String> {
+        if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+            return Ok(None);
+        }
+        let mut options = Vec::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let name = arg
+                .strip_prefix("--")
+                .ok_or_else(|| format!("unexpected argument: {arg}"))?;
+            let value = args
+                .next()
+                .ok_or_else(|| format!("missing value for --{name}"))?;
+            options.push((name, value.as_str()));
+        }
+        let option = |name: &str| {
+            options
+                .iter()
+                .find(|(option, _)| *option == name)
+                .map(|(_, value)| *value)
+        };
+
+        let mut config = match option("config") {
+            Some(path) => load_config(&read_file(path)?, option("bookmark"))?,
+            None if option("bookmark").is_some() => {
+                return Err("--bookmark requires --config".to_string())
+            }
+            None => Config::default(),
+        };
+        // the view is validated as a whole, a new corner may not fit the other one of the config
+        let mut hash = Vec::new();
+        for (name, value) in &options {
+            match *name {
+                "output" | "width" | "height" | "config" | "bookmark" => (),
+                "link" => hash.push(
+                    value
+                        .split_once('#')
+                        .map_or(*value, |(_, hash)| hash)
+                        .to_string(),
+                ),
+                "renderer" => {
+                    config.renderer = match *value {
+                        "scanline" => Renderer::Scanline,
+                        "subdivision" => Renderer::Subdivision,
+                        _ => return Err(format!("invalid renderer: {value}")),
+                    };
+                }
+                "smooth" => {
+                    config.smooth_coloring = match *value {
+                        "on" => true,
+                        "off" => false,
+                        _ => return Err(format!("invalid smooth coloring: {value}")),
+                    };
+                }
+                name if LINK_OPTIONS.contains(&name) => hash.push(format!("{name}={value}")),
+                _ => return Err(format!("unknown option: --{name}")),
+            }
+        }
+        link::apply(&mut config, hash.join("&").as_str())?;
+
+        let output = option("output")
+            .ok_or_else(|| "missing --output".to_string())?
+            .to_string();
+        let format = match output.rsplit_once('.').map(|(_, ext)| ext.to_lowercase()) {
+            Some(ext) if ext == "png" => Format::Png,
+            Some(ext) if ext == "ppm" => Format::Ppm,
+            _ => return Err(format!("unsupported output format: {output}")),
+        };
+        let width = parse_size("width", option("width"), DEFAULT_WIDTH)?;
+        // like the web app, the default height shows the area undistorted
+        let (min, max) = active_area(&config);
+        let default_height = height_for_ratio(width, min, max)
+            .ok_or_else(|| format!("invalid area: {min} - {max}"))?
+            .clamp(1, MAX_SIZE);
+        Ok(Some(Self {
+            width,
+            height: parse_size("height", option("height"), default_height)?,
+            config,
+            output,
+            format,
+        }))
+    }
+
+    #[must_use]
+    pub fn output(&self) -> &str {
+        &self.output
+    }
+
+    #[must_use]
+    pub const fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[must_use]
+    pub const fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Calculate the image and encode it. The iterations and colors are the same as in the web
+    /// app, the image is identical to one rendered there with the same config and size.
+    ///
+    /// # Errors
+    /// Fails if the image cannot be encoded.
+    pub fn render(&self, clock: Box<dyn Clock>) -> Result<Vec<u8>, String> {
+        let rgba = render_rgba(&self.config, self.width, self.height, clock);
+        match self.format {
+            Format::Png => encode_png(self.width, self.height, &rgba, &png_text(&self.config)?),
+            Format::Ppm => Ok(encode_ppm(self.width, self.height, &rgba)),
+        }
+    }
+}
+
+fn parse_size(name: &str, value: Option<&str>, default: u32) -> Result<u32, String> {
+    match value.map(str::parse::<u32>) {
+        None => Ok(default),
+        Some(Ok(size)) if (1..=MAX_SIZE).contains(&size) => Ok(size),
+        _ => Err(format!(
+            "invalid {name}, expected 1 to {MAX_SIZE} pixels"
+        )),
+    }
+}
+
+/// Read a saved config of any version, a single bookmark or a bookmark library. A bookmark only
+/// holds the view, the other settings keep their defaults. The view to render is validated.
+fn load_config(json: &str, bookmark: Option<&str>) -> Result<Config, String> {
+    let value: Value =
+        serde_json::from_str(json).map_err(|err| format!("invalid config file: {err}"))?;
+    let mut config = Config::default();
+    if value.get("entries").is_some() {
+        let bookmarks = Bookmarks::migrate(value)?;
+        let entry = bookmark.map_or_else(
+            || bookmarks.entries().first(),
+            |name| bookmarks.entries().iter().find(|entry| entry.name == name),
+        );
+        entry
+            .ok_or_else(|| format!("bookmark not found: {}", bookmark.unwrap_or_default()))?
+            .apply(&mut config);
+    } else if value.get("view").is_some() {
+        let entry: Bookmark =
+            serde_json::from_value(value).map_err(|err| format!("invalid bookmark: {err}"))?;
+        entry.apply(&mut config);
+    } else {
+        config = migrate(value)?;
+    }
+    validation::check(&config, config.active_config)?;
+    Ok(config)
+}
+
+/// RGBA pixels of a `width` x `height` image of the active fractal in `config`.
+fn render_rgba(config: &Config, width: u32, height: u32, clock: Box<dyn Clock>) -> Vec<u8> {
+    let mut fractal = create_fractal(config, width, height, clock);
+    let buffer = render_image(fractal.as_mut(), width, height, None);
+    Coloring::new(config, html_to_rgba(BACKGROUND_COLOR)).image_to_rgba(&buffer)
+}
+
+/// Encode RGBA pixels as binary PPM, the alpha channel is dropped.
+#[must_use]
+pub fn encode_ppm(width: u32, height: u32, rgba: &[u8]) -> Vec<u8> {
+    let mut data = format!("P6\n{width} {height}\n255\n").into_bytes();
+    data.reserve(rgba.len() / 4 * 3);
+    rgba.chunks_exact(4)
+        .for_each(|pixel| data.extend_from_slice(&pixel[..3]));
+    data
+}
+
+#[cfg(test)]
+mod test {
+    use super::{encode_ppm, load_config, render_rgba, Format, Job};
+    use crate::{
+        bookmarks::Bookmarks,
+        clock::SystemClock,
+        coloring::Coloring,
+        complex::Complex,
+        palette::html_to_rgba,
+        render::create_fractal,
+        scheduler::{Order, Scheduler, PASSES, TILE_SIZE},
+        Config, FractalType, Renderer, BACKGROUND_COLOR,
+    };
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(ToString::to_string).collect()
+    }
+
+    fn no_file(path: &str) -> Result<String, String> {
+        Err(format!("no such file: {}", path))
+    }
+
+    #[test]
+    fn test_parse() {
+        let job = Job::parse(
+            &args("--type julia --c -0.4,0.6 --iter 80 --width 64 --height 48 --output a.PPM"),
+            no_file,
+        )
+        .expect("valid arguments")
+        .expect("job expected");
+        assert_eq!(job.config.active_config, FractalType::JuliaSet);
+        assert_eq!(job.config.julia_set_cfg.c, Complex::new(-0.4, 0.6));
+        assert_eq!(job.config.julia_set_cfg.max_iterations, 80);
+        assert_eq!((job.width(), job.height()), (64, 48));
+        assert_eq!(job.format, Format::Ppm);
+
+        // a deep link may be given as the whole url
+        let job = Job::parse(
+            &args("--link https://x/#type=newton&iter=20 --renderer subdivision --output b.png"),
+            no_file,
+        )
+        .expect("valid arguments")
+        .expect("job expected");
+        assert_eq!(job.config.active_config, FractalType::Newton);
+        assert_eq!(job.config.newton_cfg.max_iterations, 20);
+        assert_eq!(job.config.renderer, Renderer::Subdivision);
+
+        // an area apart from the default one, both corners are checked together
+        let job = Job::parse(&args("--min 1,1 --max 2,2 --output d.png"), no_file)
+            .expect("valid arguments")
+            .expect("job expected");
+        assert_eq!(job.config.mandelbrot_cfg.c_min, Complex::new(1.0, 1.0));
+        assert_eq!(job.config.mandelbrot_cfg.c_max, Complex::new(2.0, 2.0));
+        let job = Job::parse(
+            &args("--type julia --max 5,4 --link #min=3,3 --output d.png"),
+            no_file,
+        )
+        .expect("valid arguments")
+        .expect("job expected");
+        assert_eq!(job.config.julia_set_cfg.x_min, Complex::new(3.0, 3.0));
+        let job = Job::parse(&args("--type julia --type newton --output d.png"), no_file)
+            .expect("valid arguments")
+            .expect("job expected");
+        assert_eq!(job.config.active_config, FractalType::Newton);
+
+        // the height follows the aspect ratio of the area unless it is given
+        let job = Job::parse(&args("--output c.png"), no_file)
+            .expect("valid arguments")
+            .expect("job expected");
+        assert_eq!((job.width(), job.height()), (1024, 928));
+        let job = Job::parse(&args("--type newton --width 300 --output c.png"), no_file)
+            .expect("valid arguments")
+            .expect("job expected");
+        assert_eq!(job.height(), 200);
+
+        assert!(Job::parse(&args("--help"), no_file)
+            .expect("help")
+            .is_none());
+        assert!(Job::parse(&args("--type julia"), no_file).is_err());
+        assert!(Job::parse(&args("--output a.jpg"), no_file).is_err());
+        assert!(Job::parse(&args("--output a.png --width 0"), no_file).is_err());
+        assert!(Job::parse(&args("--output a.png --iter"), no_file).is_err());
+        assert!(Job::parse(&args("--output a.png --zoom 2"), no_file).is_err());
+        assert!(Job::parse(&args("--output a.png --config a.json"), no_file).is_err());
+    }
+
+    #[test]
+    fn test_load_config() {
+        let mut saved = Config {
+            active_config: FractalType::BurningShip,
+            smooth_coloring: false,
+            ..Config::default()
+        };
+        saved.burning_ship_cfg.max_iterations = 99;
+        let json = serde_json::to_string(&saved).expect("serializable config");
+        let config = load_config(&json, None).expect("valid config");
+        assert_eq!(config.burning_ship_cfg.max_iterations, 99);
+        assert!(!config.smooth_coloring);
+
+        let mut bookmarks = Bookmarks::default();
+        bookmarks.add("first", &Config::default(), String::new());
+        bookmarks.add("ship", &saved, String::new());
+        let json = bookmarks.to_json().expect("serializable bookmarks");
+        let config = load_config(&json, Some("ship")).expect("valid bookmark");
+        assert_eq!(config.active_config, FractalType::BurningShip);
+        assert_eq!(config.burning_ship_cfg.max_iterations, 99);
+        // only the view is bookmarked
+        assert!(config.smooth_coloring);
+        let config = load_config(&json, None).expect("valid bookmark");
+        assert_eq!(config.active_config, FractalType::Mandelbrot);
+        assert!(load_config(&json, Some("missing")).is_err());
+
+        let json = serde_json::to_string(&bookmarks.entries()[1]).expect("serializable bookmark");
+        let config = load_config(&json, None).expect("valid bookmark");
+        assert_eq!(config.burning_ship_cfg.max_iterations, 99);
+
+        let job = Job::parse(&args("--config ship.json --iter 5 --output a.png"), |_| {
+            Ok(json.clone())
+        })
+        .expect("valid arguments")
+        .expect("job expected");
+        assert_eq!(job.config.burning_ship_cfg.max_iterations, 5);
+    }
+
+    #[test]
+    fn test_load_invalid_config() {
+        let mut config = Config::default();
+        config.mandelbrot_cfg.max_iterations = 0;
+        let json = serde_json::to_string(&config).expect("serializable config");
+        assert!(load_config(&json, None).is_err());
+
+        let mut bookmarks = Bookmarks::default();
+        bookmarks.add("no iterations", &config, String::new());
+        let json = serde_json::to_string(&bookmarks.entries()[0]).expect("serializable bookmark");
+        assert!(load_config(&json, None).is_err());
+        assert!(Job::parse(&args("--config bm.json --output a.png"), |_| Ok(
+            json.clone()
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn test_render() {
+        let mut config = Config::default();
+        config.mandelbrot_cfg.max_iterations = 40;
+        let rgba = render_rgba(&config, 16, 8, Box::new(SystemClock::new()));
+        assert_eq!(rgba.len(), 16 * 8 * 4);
+        // the centre row crosses the set, points inside get the black background
+        assert!(rgba.chunks_exact(4).any(|pixel| pixel == [0, 0, 0, 0xFF]));
+        assert!(rgba.chunks_exact(4).all(|pixel| pixel[3] == 0xFF));
+
+        let ppm = encode_ppm(16, 8, &rgba);
+        let header = b"P6\n16 8\n255\n";
+        assert_eq!(&ppm[..header.len()], header);
+        assert_eq!(ppm.len(), header.len() + 16 * 8 * 3);
+        assert_eq!(ppm[header.len()..header.len() + 3], rgba[0..3]);
+    }
+
+    #[test]
+    fn test_canvas_colors() {
+        // draw the tiles of every pass like the web app, the final image matches the native render
+        let (width, height) = (150, 90);
+        for fractal_type in [FractalType::JuliaSet, FractalType::Newton] {
+            let config = Config {
+                active_config: fractal_type,
+                ..Config::default()
+            };
+            let mut fractal = create_fractal(&config, width, height, Box::new(SystemClock::new()));
+            let coloring = Coloring::new(&config, html_to_rgba(BACKGROUND_COLOR));
+            let mut scheduler = Scheduler::new(width, height, TILE_SIZE, Order::CenterOut, &PASSES);
+            let mut canvas = vec![0; (width * height * 4) as usize];
+            while let Some(tile) = scheduler.next() {
+                let points = fractal.calculate(&tile, None);
+                let rgba = coloring.tile_to_rgba(&points);
+                for (row, pixels) in rgba.chunks_exact(tile.width as usize * 4).enumerate() {
+                    let start = (((tile.y_start as usize + row) * width as usize)
+                        + tile.x_start as usize)
+                        * 4;
+                    canvas[start..start + pixels.len()].copy_from_slice(pixels);
+                }
+                scheduler.complete(&tile);
+            }
+            assert!(scheduler.is_done());
+            let native = render_rgba(&config, width, height, Box::new(SystemClock::new()));
+            assert!(canvas == native, "{:?} pixels differ", fractal_type);
+        }
+    }
+}
@@ -0,0 +1,528 @@
+#![allow(clippy::missing_const_for_fn)]
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter, Write};
+use std::ops::{Add, Neg, Sub};
+use std::str::FromStr;
+
+use super::complex::Complex;
+
+// every limb holds 9 decimal digits, decimal strings convert without rounding
+const BASE: u32 = 1_000_000_000;
+const LIMB_DIGITS: usize = 9;
+
+// bounds the size of parsed numbers, deep zooms stay far below
+const MAX_EXPONENT: i64 = 10_000;
+
+/// Signed decimal fixed point number of arbitrary precision, pure Rust so it also runs in wasm.
+/// Additions are exact, multiplications truncate to a requested number of decimal places.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct BigFloat {
+    negative: bool,
+    // base 10^9, least significant limb first, the lowest `frac` limbs are the fractional part
+    limbs: Vec<u32>,
+    frac: usize,
+}
+
+impl BigFloat {
+    /// The decimal value closest to `value` with the fewest digits, non-finite values give zero.
+    pub fn from_f64(value: f64) -> Self {
+        format!("{value:e}").parse().unwrap_or_default()
+    }
+
+    /// Correctly rounded to the nearest f64.
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+
+    // the three most significant limbs, within a few ulp, fast enough for every iteration
+    #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+    fn approx_f64(&self) -> f64 {
+        let end = self
+            .limbs
+            .iter()
+            .rposition(|limb| *limb != 0)
+            .map_or(0, |idx| idx + 1);
+        let start = end.saturating_sub(3);
+        let mantissa = self.limbs[start..end]
+            .iter()
+            .rev()
+            .fold(0.0_f64, |res, limb| {
+                res.mul_add(f64::from(BASE), f64::from(*limb))
+            });
+        let value = mantissa * f64::from(BASE).powi(start as i32 - self.frac as i32);
+        if self.negative {
+            -value
+        } else {
+            value
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    /// Product truncated towards zero to at least `digits` decimal places.
+    pub fn mul(&self, other: &Self, digits: usize) -> Self {
+        if self.is_zero() || other.is_zero() {
+            return Self::default();
+        }
+        let mut res = vec![0u64; self.limbs.len() + other.limbs.len()];
+        for (i, a) in self.limbs.iter().enumerate() {
+            let mut carry = 0;
+            for (j, b) in other.limbs.iter().enumerate() {
+                let sum = res[i + j] + u64::from(*a) * u64::from(*b) + carry;
+                res[i + j] = sum % u64::from(BASE);
+                carry = sum / u64::from(BASE);
+            }
+            res[i + other.limbs.len()] = carry;
+        }
+        let frac = self.frac + other.frac;
+        let drop = frac.saturating_sub(limbs_for(digits));
+        #[allow(clippy::cast_possible_truncation)]
+        Self::normalized(
+            self.negative != other.negative,
+            res[drop..].iter().map(|limb| *limb as u32).collect(),
+            frac - drop,
+        )
+    }
+
+    /// Truncated towards zero to at most `digits` decimal places.
+    pub fn truncate(&self, digits: usize) -> Self {
+        let keep = limbs_for(digits);
+        if self.frac * LIMB_DIGITS <= digits {
+            return self.clone();
+        }
+        let drop = self.frac - keep;
+        let mut limbs = self.limbs[drop.min(self.limbs.len())..].to_vec();
+        if let Some(lowest) = limbs.first_mut() {
+            #[allow(clippy::cast_possible_truncation)]
+            let unit = 10u32.pow((keep * LIMB_DIGITS - digits) as u32);
+            *lowest -= *lowest % unit;
+        }
+        Self::normalized(self.negative, limbs, keep)
+    }
+
+    /// Rounded half away from zero to at most `digits` decimal places.
+    pub fn round(&self, digits: usize) -> Self {
+        if self.frac * LIMB_DIGITS <= digits {
+            return self.clone();
+        }
+        let half = Self {
+            negative: self.negative,
+            ..format!("5e-{}", digits + 1).parse().unwrap_or_default()
+        };
+        (self + &half).truncate(digits)
+    }
+
+    // strips leading and trailing zero limbs so equal values compare equal
+    fn normalized(negative: bool, mut limbs: Vec<u32>, mut frac: usize) -> Self {
+        while limbs.len() > frac && limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        let zeros = limbs
+            .iter()
+            .take(frac)
+            .take_while(|limb| **limb == 0)
+            .count();
+        limbs.drain(..zeros);
+        frac -= zeros;
+        if limbs.iter().all(|limb| *limb == 0) {
+            return Self::default();
+        }
+        Self {
+            negative,
+            limbs,
+            frac,
+        }
+    }
+
+    // limbs of the magnitude, padded to `frac` fractional and `len` limbs in total
+    fn aligned(&self, frac: usize, len: usize) -> Vec<u32> {
+        let mut limbs = vec![0; frac - self.frac];
+        limbs.extend_from_slice(&self.limbs);
+        limbs.resize(len, 0);
+        limbs
+    }
+
+    fn add_signed(&self, other: &Self, other_negative: bool) -> Self {
+        let frac = self.frac.max(other.frac);
+        let len = (self.limbs.len() - self.frac).max(other.limbs.len() - other.frac) + frac + 1;
+        let (a, b) = (self.aligned(frac, len), other.aligned(frac, len));
+        if self.negative == other_negative {
+            let mut carry = 0;
+            let limbs = a
+                .iter()
+                .zip(b.iter())
+                .map(|(a, b)| {
+                    let sum = a + b + carry;
+                    carry = sum / BASE;
+                    sum % BASE
+                })
+                .collect();
+            return Self::normalized(self.negative, limbs, frac);
+        }
+
+        // subtract the smaller magnitude, the result takes the sign of the larger one
+        let (larger, smaller, negative) = match a.iter().rev().cmp(b.iter().rev()) {
+            Ordering::Less => (b, a, other_negative),
+            _ => (a, b, self.negative),
+        };
+        let mut borrow = 0;
+        let limbs = larger
+            .iter()
+            .zip(smaller.iter())
+            .map(|(a, b)| {
+                let sub = b + borrow;
+                if *a >= sub {
+                    borrow = 0;
+                    a - sub
+                } else {
+                    borrow = 1;
+                    a + BASE - sub
+                }
+            })
+            .collect();
+        Self::normalized(negative, limbs, frac)
+    }
+}
+
+// number of limbs holding `digits` decimal places
+fn limbs_for(digits: usize) -> usize {
+    digits.div_ceil(LIMB_DIGITS)
+}
+
+impl Add for &BigFloat {
+    type Output = BigFloat;
+
+    fn add(self, other: Self) -> BigFloat {
+        self.add_signed(other, other.negative)
+    }
+}
+
+impl Sub for &BigFloat {
+    type Output = BigFloat;
+
+    fn sub(self, other: Self) -> BigFloat {
+        self.add_signed(other, !other.negative)
+    }
+}
+
+impl Neg for &BigFloat {
+    type Output = BigFloat;
+
+    fn neg(self) -> BigFloat {
+        BigFloat {
+            negative: !self.negative && !self.is_zero(),
+            ..self.clone()
+        }
+    }
+}
+
+impl FromStr for BigFloat {
+    type Err = String;
+
+    /// Decimal numbers like `-0.75`, `.5` or `1.25e-30`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid number: {value}");
+        let (mantissa, exponent) = match value.find(['e', 'E']) {
+            Some(idx) => (
+                &value[..idx],
+                value[idx + 1..].parse::<i64>().map_err(|_| invalid())?,
+            ),
+            None => (value, 0),
+        };
+        if !(-MAX_EXPONENT..=MAX_EXPONENT).contains(&exponent) {
+            return Err(invalid());
+        }
+        let (negative, mantissa) = mantissa.strip_prefix('-').map_or_else(
+            || (false, mantissa.strip_prefix('+').unwrap_or(mantissa)),
+            |rest| (true, rest),
+        );
+        let (int, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        if (int.is_empty() && fraction.is_empty())
+            || !int
+                .chars()
+                .chain(fraction.chars())
+                .all(|ch| ch.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+
+        // move the decimal point by the exponent, then pad both parts to whole limbs
+        #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+        let point = int.len() as i64 + exponent;
+        let digits = format!("{int}{fraction}");
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let (int_digits, lead) = if point < 0 {
+            (0, (-point) as usize)
+        } else {
+            (point as usize, 0)
+        };
+        let mut padded = "0".repeat(lead);
+        padded.push_str(&digits);
+        if padded.len() < int_digits {
+            padded.push_str(&"0".repeat(int_digits - padded.len()));
+        }
+        let frac = limbs_for(padded.len() - int_digits);
+        let int_pad = limbs_for(int_digits) * LIMB_DIGITS - int_digits;
+        let mut padded = format!("{}{}", "0".repeat(int_pad), padded);
+        padded.push_str(&"0".repeat(frac * LIMB_DIGITS + int_digits + int_pad - padded.len()));
+
+        let limbs = padded
+            .as_bytes()
+            .rchunks(LIMB_DIGITS)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .fold(0, |res, digit| res * 10 + u32::from(digit - b'0'))
+            })
+            .collect();
+        Ok(Self::normalized(negative, limbs, frac))
+    }
+}
+
+impl Display for BigFloat {
+    /// Exact decimal representation without trailing zeros.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.negative {
+            write!(f, "-")?;
+        }
+        let (fraction, int) = self.limbs.split_at(self.frac);
+        match int.split_last() {
+            Some((first, rest)) => {
+                write!(f, "{first}")?;
+                for limb in rest.iter().rev() {
+                    write!(f, "{limb:09}")?;
+                }
+            }
+            None => write!(f, "0")?,
+        }
+        if !fraction.is_empty() {
+            let digits = fraction.iter().rev().fold(String::new(), |mut digits, limb| {
+                let _ = write!(digits, "{limb:09}");
+                digits
+            });
+            write!(f, ".{}", digits.trim_end_matches('0'))?;
+        }
+        Ok(())
+    }
+}
+
+impl From<BigFloat> for String {
+    fn from(value: BigFloat) -> Self {
+        value.to_string()
+    }
+}
+
+impl TryFrom<String> for BigFloat {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// Complex number of two `BigFloat`s.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BigComplex {
+    real: BigFloat,
+    imag: BigFloat,
+}
+
+impl BigComplex {
+    pub fn new(real: BigFloat, imag: BigFloat) -> Self {
+        Self { real, imag }
+    }
+
+    pub fn real(&self) -> &BigFloat {
+        &self.real
+    }
+
+    pub fn imag(&self) -> &BigFloat {
+        &self.imag
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.real.is_zero() && self.imag.is_zero()
+    }
+
+    pub fn to_complex(&self) -> Complex {
+        Complex::new(self.real.to_f64(), self.imag.to_f64())
+    }
+
+    /// Within a few ulp, used while iterating.
+    pub fn approx_complex(&self) -> Complex {
+        Complex::new(self.real.approx_f64(), self.imag.approx_f64())
+    }
+
+    pub fn truncate(&self, digits: usize) -> Self {
+        Self::new(self.real.truncate(digits), self.imag.truncate(digits))
+    }
+
+    pub fn round(&self, digits: usize) -> Self {
+        Self::new(self.real.round(digits), self.imag.round(digits))
+    }
+
+    /// Product truncated to at least `digits` decimal places.
+    pub fn mul(&self, other: &Self, digits: usize) -> Self {
+        let real = &self.real.mul(&other.real, digits) - &self.imag.mul(&other.imag, digits);
+        let imag = &self.real.mul(&other.imag, digits) + &self.imag.mul(&other.real, digits);
+        Self::new(real, imag)
+    }
+
+    /// Square truncated to at least `digits` decimal places.
+    pub fn square(&self, digits: usize) -> Self {
+        let cross = self.real.mul(&self.imag, digits);
+        Self::new(
+            &self.real.mul(&self.real, digits) - &self.imag.mul(&self.imag, digits),
+            &cross + &cross,
+        )
+    }
+
+    pub fn powi(&self, exp: u32, digits: usize) -> Self {
+        // square and multiply
+        let mut res = Self::new("1".parse().unwrap_or_default(), BigFloat::default());
+        let mut base = self.clone();
+        let mut exp = exp;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                res = res.mul(&base, digits);
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = base.square(digits);
+            }
+        }
+        res
+    }
+}
+
+impl From<Complex> for BigComplex {
+    fn from(value: Complex) -> Self {
+        Self::new(
+            BigFloat::from_f64(value.real()),
+            BigFloat::from_f64(value.imag()),
+        )
+    }
+}
+
+impl Add for &BigComplex {
+    type Output = BigComplex;
+
+    fn add(self, other: Self) -> BigComplex {
+        BigComplex::new(&self.real + &other.real, &self.imag + &other.imag)
+    }
+}
+
+impl Sub for &BigComplex {
+    type Output = BigComplex;
+
+    fn sub(self, other: Self) -> BigComplex {
+        BigComplex::new(&self.real - &other.real, &self.imag - &other.imag)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BigComplex, BigFloat};
+    use crate::complex::Complex;
+
+    fn big(value: &str) -> BigFloat {
+        value.parse().expect("invalid number")
+    }
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!(big("0").to_string(), "0");
+        assert_eq!(big("-0.000").to_string(), "0");
+        assert_eq!(big("+12.50").to_string(), "12.5");
+        assert_eq!(big(".5").to_string(), "0.5");
+        assert_eq!(big("-1234567890123.25").to_string(), "-1234567890123.25");
+        assert_eq!(big("1.5e-20").to_string(), "0.000000000000000000015");
+        assert_eq!(big("-2.5E3").to_string(), "-2500");
+        assert_eq!(big("7e10").to_string(), "70000000000");
+        assert_eq!(big("1000000000").to_string(), "1000000000");
+        for invalid in [
+            "", ".", "-", "1.2.3", "1e", "abc", "inf", "NaN", "1e99999", "0x10",
+        ] {
+            assert!(invalid.parse::<BigFloat>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_f64() {
+        for value in [
+            0.0,
+            -0.75,
+            0.1,
+            1.0 / 3.0,
+            -1.234_567_890_123_456_7e-25,
+            6.02e23,
+        ] {
+            assert_eq!(
+                BigFloat::from_f64(value).to_f64().to_bits(),
+                value.to_bits()
+            );
+            let approx = BigFloat::from_f64(value).approx_f64();
+            assert!((approx - value).abs() <= value.abs() * 1e-15, "{}", value);
+        }
+        assert_eq!(BigFloat::from_f64(-0.1).to_string(), "-0.1");
+        assert_eq!(BigFloat::from_f64(f64::NAN), BigFloat::default());
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(&big("0.999999999") + &big("0.000000001"), big("1"));
+        assert_eq!(&big("1") - &big("1.000000000000000001"), big("-1e-18"));
+        assert_eq!(&big("-2.5") + &big("1.25"), big("-1.25"));
+        assert_eq!(&big("-2.5") - &big("-2.5"), BigFloat::default());
+        assert_eq!(-&big("3"), big("-3"));
+
+        // digits far beyond f64 survive
+        let tiny = big("1e-40");
+        let sum = &big("-0.75") + &tiny;
+        assert_eq!(&sum - &big("-0.75"), tiny);
+        assert_eq!(sum.to_f64(), -0.75);
+
+        assert_eq!(big("1.5").mul(&big("-2.5"), 10), big("-3.75"));
+        assert_eq!(
+            big("123456789.987654321").mul(&big("1000"), 20),
+            big("123456789987.654321")
+        );
+        assert_eq!(big("1e-30").mul(&big("1e-30"), 60), big("1e-60"));
+        // truncated to whole limbs of 9 digits
+        assert_eq!(big("1e-30").mul(&big("1e-30"), 50), BigFloat::default());
+        let third = big("0.333333333333333333333333333");
+        assert_eq!(third.mul(&big("3"), 18), big("0.999999999999999999"));
+    }
+
+    #[test]
+    fn test_round() {
+        assert_eq!(big("1.23456").truncate(3), big("1.234"));
+        assert_eq!(big("-1.23456").truncate(3), big("-1.234"));
+        assert_eq!(big("1.23456").round(3), big("1.235"));
+        assert_eq!(big("-1.9999999999999").round(2), big("-2"));
+        assert_eq!(big("0.0004").round(3), BigFloat::default());
+        assert_eq!(big("12.5").round(20), big("12.5"));
+    }
+
+    #[test]
+    fn test_complex() {
+        let value = BigComplex::from(Complex::new(0.5, -1.5));
+        assert_eq!(value.square(10).to_complex(), Complex::new(-2.0, -1.5));
+        assert_eq!(
+            value.powi(3, 10).to_complex(),
+            Complex::new(0.5, -1.5).powi(3)
+        );
+        assert_eq!((&value - &value), BigComplex::default());
+
+        let json = serde_json::to_string(&value).expect("failed to serialize");
+        assert_eq!(json, r#"{"real":"0.5","imag":"-1.5"}"#);
+        let res: BigComplex = serde_json::from_str(&json).expect("failed to deserialize");
+        assert_eq!(res, value);
+        assert!(serde_json::from_str::<BigComplex>(r#"{"real":"x","imag":"0"}"#).is_err());
+    }
+}
@@ -0,0 +1,37 @@
+use std::{fs, process, time::Instant};
+
+use seed_fractals::{
+    batch::{Job, USAGE},
+    clock::SystemClock,
+};
+
+fn run(args: &[String]) -> Result<(), String> {
+    let Some(job) = Job::parse(args, |path| {
+        fs::read_to_string(path).map_err(|err| format!("failed to read {path}: {err}"))
+    })?
+    else {
+        print!("{USAGE}");
+        return Ok(());
+    };
+
+    let start = Instant::now();
+    let data = job.render(Box::new(SystemClock::new()))?;
+    fs::write(job.output(), data)
+        .map_err(|err| format!("failed to write {}: {}", job.output(), err))?;
+    println!(
+        "rendered {} x {} pixels to {} in {:.2}s",
+        job.width(),
+        job.height(),
+        job.output(),
+        start.elapsed().as_secs_f64()
+    );
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Err(err) = run(&args) {
+        eprintln!("error: {err}\n\n{USAGE}");
+        process::exit(1);
+    }
+}
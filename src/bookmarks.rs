@@ -0,0 +1,230 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{migration::stored_version, validation, BurningShipCfg, Config, FractalType, JuliaSetCfg, MandelbrotCfg, NewtonCfg};
+
+/// Parameters and area of one fractal type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum View {
+    JuliaSet(JuliaSetCfg),
+    Mandelbrot(MandelbrotCfg),
+    BurningShip(BurningShipCfg),
+    Newton(NewtonCfg),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub name: String,
+    pub view: View,
+    // image as png data url, empty if nothing was rendered when the bookmark was saved
+    pub thumbnail: String,
+}
+
+impl Bookmark {
+    /// Make the view of the bookmark the active config.
+    pub fn apply(&self, config: &mut Config) {
+        match &self.view {
+            View::JuliaSet(cfg) => {
+                config.active_config = FractalType::JuliaSet;
+                config.julia_set_cfg = cfg.clone();
+            }
+            View::Mandelbrot(cfg) => {
+                config.active_config = FractalType::Mandelbrot;
+                config.mandelbrot_cfg = cfg.clone();
+            }
+            View::BurningShip(cfg) => {
+                config.active_config = FractalType::BurningShip;
+                config.burning_ship_cfg = cfg.clone();
+            }
+            View::Newton(cfg) => {
+                config.active_config = FractalType::Newton;
+                config.newton_cfg = cfg.clone();
+            }
+        }
+    }
+
+    /// Check the parameters and area of the view, e.g. of an imported bookmark.
+    pub fn check(&self) -> Result<(), String> {
+        let mut config = Config::default();
+        self.apply(&mut config);
+        validation::check(&config, config.active_config)
+    }
+}
+
+/// Version of the stored and exported library layout, increment it and add a migration to
+/// `Bookmarks::migrate` when changing `Bookmarks` or `View`.
+pub const BOOKMARKS_VERSION: u32 = 1;
+
+/// Library of saved views, stored in `LocalStorage` next to `Config`.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Bookmarks {
+    version: u32,
+    entries: Vec<Bookmark>,
+}
+
+impl Default for Bookmarks {
+    fn default() -> Self {
+        Self {
+            version: BOOKMARKS_VERSION,
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl Bookmarks {
+    /// Read a stored or exported library of any known version.
+    pub fn migrate(mut value: Value) -> Result<Self, String> {
+        let version = stored_version(&value)?;
+        if version > BOOKMARKS_VERSION {
+            return Err(format!(
+                "bookmarks version {version} is newer than the supported version {BOOKMARKS_VERSION}"
+            ));
+        }
+        // the first layout only lacked the version
+        if let Some(library) = value.as_object_mut() {
+            library.insert("version".to_string(), BOOKMARKS_VERSION.into());
+        }
+        serde_json::from_value(value).map_err(|err| format!("invalid bookmarks: {err}"))
+    }
+
+    pub fn entries(&self) -> &[Bookmark] {
+        &self.entries
+    }
+
+    /// Save the active view of `config` under `name`.
+    pub fn add(&mut self, name: &str, config: &Config, thumbnail: String) {
+        let view = match config.active_config {
+            FractalType::JuliaSet => View::JuliaSet(config.julia_set_cfg.clone()),
+            FractalType::Mandelbrot => View::Mandelbrot(config.mandelbrot_cfg.clone()),
+            FractalType::BurningShip => View::BurningShip(config.burning_ship_cfg.clone()),
+            FractalType::Newton => View::Newton(config.newton_cfg.clone()),
+        };
+        self.entries.push(Bookmark {
+            name: name.to_string(),
+            view,
+            thumbnail,
+        });
+    }
+
+    pub fn rename(&mut self, idx: usize, name: &str) {
+        if let Some(bookmark) = self.entries.get_mut(idx) {
+            bookmark.name = name.to_string();
+        }
+    }
+
+    pub fn remove(&mut self, idx: usize) {
+        if idx < self.entries.len() {
+            self.entries.remove(idx);
+        }
+    }
+
+    /// Make the bookmarked view the active config, returns false if there is no such bookmark.
+    pub fn restore(&self, idx: usize, config: &mut Config) -> bool {
+        let Some(bookmark) = self.entries.get(idx) else {
+            return false;
+        };
+        bookmark.apply(config);
+        true
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|err| format!("failed to export bookmarks: {err}"))
+    }
+
+    /// Append the valid bookmarks of an exported library,
+    /// returns the number of bookmarks added and of invalid ones skipped.
+    pub fn import(&mut self, json: &str) -> Result<(usize, usize), String> {
+        let imported = serde_json::from_str(json)
+            .map_err(|err| err.to_string())
+            .and_then(Self::migrate)
+            .map_err(|err| format!("failed to import bookmarks: {err}"))?;
+        let count = imported.entries.len();
+        let (valid, invalid): (Vec<Bookmark>, Vec<Bookmark>) = imported
+            .entries
+            .into_iter()
+            .partition(|bookmark| bookmark.check().is_ok());
+        self.entries.extend(valid);
+        Ok((count - invalid.len(), invalid.len()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Bookmarks, BOOKMARKS_VERSION};
+    use crate::{complex::Complex, Config, FractalType};
+
+    #[test]
+    fn test_restore() {
+        let mut config = Config {
+            active_config: FractalType::JuliaSet,
+            ..Config::default()
+        };
+        config.julia_set_cfg.c = Complex::new(0.3, -0.2);
+
+        let mut bookmarks = Bookmarks::default();
+        bookmarks.add("first", &config, String::new());
+        bookmarks.rename(0, "renamed");
+        assert_eq!(bookmarks.entries()[0].name, "renamed");
+
+        let mut restored = Config::default();
+        assert!(bookmarks.restore(0, &mut restored));
+        assert_eq!(restored.active_config, FractalType::JuliaSet);
+        assert_eq!(restored.julia_set_cfg, config.julia_set_cfg);
+        assert!(!bookmarks.restore(1, &mut restored));
+
+        bookmarks.remove(0);
+        assert!(bookmarks.entries().is_empty());
+    }
+
+    #[test]
+    fn test_import_export() {
+        let mut bookmarks = Bookmarks::default();
+        bookmarks.add("mandelbrot", &Config::default(), "data:image/png;base64,".to_string());
+        let json = bookmarks.to_json().expect("export failed");
+
+        let mut imported = Bookmarks::default();
+        assert_eq!(imported.import(json.as_str()), Ok((1, 0)));
+        assert_eq!(imported, bookmarks);
+        assert_eq!(imported.import(json.as_str()), Ok((1, 0)));
+        assert_eq!(imported.entries().len(), 2);
+
+        assert!(imported.import("{\"entries\": 5}").is_err());
+        assert_eq!(imported.entries().len(), 2);
+    }
+
+    #[test]
+    fn test_migrate() {
+        let mut bookmarks = Bookmarks::default();
+        bookmarks.add("mandelbrot", &Config::default(), String::new());
+        let mut value = serde_json::to_value(&bookmarks).expect("serializable bookmarks");
+        assert_eq!(value["version"], BOOKMARKS_VERSION);
+
+        // libraries saved before the version was added
+        value
+            .as_object_mut()
+            .expect("object expected")
+            .remove("version");
+        assert_eq!(Bookmarks::migrate(value.clone()), Ok(bookmarks));
+
+        value["version"] = (BOOKMARKS_VERSION + 1).into();
+        assert!(Bookmarks::migrate(value).is_err());
+    }
+
+    #[test]
+    fn test_import_invalid() {
+        let mut config = Config::default();
+        let mut bookmarks = Bookmarks::default();
+        bookmarks.add("valid", &config, String::new());
+        config.mandelbrot_cfg.max_iterations = 0;
+        bookmarks.add("no iterations", &config, String::new());
+        config.mandelbrot_cfg.max_iterations = 400;
+        config.mandelbrot_cfg.c_max = config.mandelbrot_cfg.c_min + Complex::new(1e-9, 1.0);
+        bookmarks.add("narrow", &config, String::new());
+        let json = bookmarks.to_json().expect("export failed");
+
+        let mut imported = Bookmarks::default();
+        assert_eq!(imported.import(json.as_str()), Ok((1, 2)));
+        assert_eq!(imported.entries().len(), 1);
+        assert_eq!(imported.entries()[0].name, "valid");
+    }
+}
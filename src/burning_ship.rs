@@ -0,0 +1,103 @@
+use super::util::{find_escape_radius, smooth_iterations, SMOOTH_BAILOUT};
+
+use super::{
+    clock::Clock,
+    complex::Complex,
+    fractal::{Fractal, KnownPoints, Points, Tile},
+    stats::Stats,
+    Renderer,
+};
+
+/// Area and iteration settings of a Burning Ship image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BurningShipParams {
+    pub c_min: Complex,
+    pub c_max: Complex,
+    pub iterations: u32,
+}
+
+pub struct BurningShip {
+    scale_real: f64,
+    scale_imag: f64,
+    offset: Complex,
+    width: u32,
+    height: u32,
+    iterations: u32,
+    known: Option<KnownPoints>,
+    clock: Box<dyn Clock>,
+}
+
+impl BurningShip {
+    pub fn new(params: &BurningShipParams, width: u32, height: u32, clock: Box<dyn Clock>) -> Self {
+        let scale_real = (params.c_max.real() - params.c_min.real()) / f64::from(width);
+        let scale_imag = (params.c_max.imag() - params.c_min.imag()) / f64::from(height);
+
+        Self {
+            scale_real,
+            scale_imag,
+            offset: params.c_min,
+            width,
+            height,
+            iterations: params.iterations,
+            known: None,
+            clock,
+        }
+    }
+
+    fn iterate(&self, c: &Complex) -> (u32, f32) {
+        // |x| has the same norm as x, so the mandelbrot escape radius applies unchanged
+        let max = f64::max(find_escape_radius(c.norm(), 2.0), SMOOTH_BAILOUT).powi(2);
+        let mut x = Complex::new(0.0, 0.0);
+        let mut last: Option<(u32, f32)> = None;
+        for idx in 1..=self.iterations {
+            x = Complex::new(x.real().abs(), x.imag().abs());
+            x = x * x + *c;
+            if x.square_length() >= max {
+                last = Some((idx, smooth_iterations(idx, x.square_length(), 2.0)));
+                break;
+            }
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        last.unwrap_or((self.iterations + 1, (self.iterations + 1) as f32))
+    }
+}
+
+impl Fractal for BurningShip {
+    fn calculate(&mut self, tile: &Tile, stats: Option<&mut Stats>) -> Points {
+        let start = self.clock.now();
+        let mut iterations = 0usize;
+
+        let mut points = Points::new(*tile);
+        for idx in 0..tile.num_points() {
+            let (x, y) = tile.position(idx);
+            let (value, smooth) = if let Some((value, smooth, _)) =
+                self.known.as_ref().and_then(|known| known.get(x, y))
+            {
+                (value, smooth)
+            } else {
+                let res = self.iterate(&Complex::new(
+                    f64::from(x).mul_add(self.scale_real, self.offset.real()),
+                    f64::from(y).mul_add(self.scale_imag, self.offset.imag()),
+                ));
+                iterations += res.0 as usize;
+                res
+            };
+            points.values[idx] = value;
+            points.smooth[idx] = smooth;
+        }
+
+        if let Some(stats) = stats {
+            stats.update(iterations, points.values.len(), start, self.clock.now());
+        }
+
+        points
+    }
+
+    fn set_known_points(&mut self, known: KnownPoints) {
+        self.known = Some(known);
+    }
+
+    // the set is not connected, a uniform border can enclose escaping detail
+    fn set_renderer(&mut self, _renderer: Renderer) {}
+}
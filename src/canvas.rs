@@ -1,50 +1,35 @@
-use crate::{FractalType, Model, BACKGROUND_COLOR};
+use crate::Model;
 use seed::log;
 
 use seed::{prelude::*, canvas}; // window
 
-use super::fractal::Points;
+use super::coloring::Coloring;
+use super::fractal::{IterationBuffer, Points};
+use super::palette::{html_to_rgba, rgb_to_html};
 use seed::prelude::web_sys::{HtmlCanvasElement, ImageData, };
-use seed::prelude::JsValue;
-// use web_sys::Navigator;
+use seed::prelude::{wasm_bindgen::Clamped, JsValue};
 
 const COLOR_MAX: u32 = 0x00FF_FFFF;
 const COLOR_MIN: u32 = 0x00FF_FFFF;
 
 const START_HUE: u32 = 0;
-const DEFAULT_SATURATION: f32 = 1.0;
-const DEFAULT_LIGHTNESS: f32 = 0.5;
-
-const HUE_OFFSET: f32 = 0.0;
-const HUE_RANGE: f32 = 300.0;
 
+#[allow(clippy::struct_field_names)]
 pub struct Canvas {
     canvas: HtmlCanvasElement,
-    steps: u32,
     width: u32,
+    coloring: Coloring,
 }
 
 impl Canvas {
     pub fn new(model: &Model) -> Self {
         Self {
             canvas: canvas("canvas").expect("Canvas not found"),
-            steps: match model.config.active_config {
-                FractalType::JuliaSet => model.config.julia_set_cfg.max_iterations,
-                FractalType::Mandelbrot => model.config.mandelbrot_cfg.max_iterations,
-            },
             width: model.width,
+            coloring: Coloring::new(&model.config, html_to_rgba(model.background_color.as_str())),
         }
     }
 
-/*    pub fn copy_to_clipboard(&self) -> bool {
-        let bla = window()
-            .navigator()
-            .permissions().expect("no permissipons found in navigator")
-            .query("name","clipboard-write");
-        false
-    }
-*/
-
     pub fn clear_canvas(&self, model: &Model) {
         log!("Clear Canvas");
 
@@ -64,34 +49,27 @@ impl Canvas {
         // ctx.stroke();
     }
 
+    /// Draw the points of a tile, a point of a coarse pass fills its block.
     pub fn draw_results(&self, points: &Points) {
-        let mut x = points.x_start;
-        let mut y = points.y_start;
-        let ctx = seed::canvas_context_2d(&self.canvas);
-        ctx.set_fill_style(&JsValue::from_str("FFFFFF"));
-
-        let mut last_color = "".to_string();
-        points.values[0..points.num_points]
-            .iter()
-            .for_each(|value| {
-                let color = if *value >= self.steps - 1 {
-                    BACKGROUND_COLOR.to_string()
-                } else {
-                    self.iterations_as_hue_to_rgb(*value)
-                };
-                if color != last_color {
-                    // log!(format!("draw_result: color: {} pos: {},{}", color, x, y));
-                    ctx.set_fill_style(&JsValue::from_str(color.as_str()));
-                    last_color = color;
-                }
-                ctx.fill_rect(x.into(), y.into(), 1.0, 1.0);
-
-                x += 1;
-                if x >= self.width {
-                    x = 0;
-                    y += 1;
-                }
-            });
+        let tile = &points.tile;
+        let buffer = self.coloring.tile_to_rgba(points);
+        self.put_image(&buffer, tile.x_start, tile.y_start, tile.width, tile.height);
+    }
+
+    /// Draw all points in `buffer` with the current colors, points not calculated yet are
+    /// drawn in the background color.
+    pub fn recolor(&self, buffer: &IterationBuffer) {
+        let colors = self.coloring.image_to_rgba(buffer);
+        self.put_image(&colors, 0, 0, buffer.width(), buffer.height());
+    }
+
+    fn put_image(&self, rgba: &[u8], x: u32, y: u32, width: u32, height: u32) {
+        let image_data =
+            ImageData::new_with_u8_clamped_array_and_sh(Clamped(rgba), width, height)
+                .expect("failed to create image data");
+        seed::canvas_context_2d(&self.canvas)
+            .put_image_data(&image_data, x.into(), y.into())
+            .expect("cannot draw image data");
     }
 
     pub fn draw_frame(&self, x_start: u32, y_start: u32, x_end: u32, y_end: u32) -> ImageData {
@@ -109,16 +87,7 @@ impl Canvas {
                 .expect("failed to retrieve image data")
                 .dyn_into::<ImageData>().expect("Failed to cast to ImageData");
         */
-        let image_data = ctx
-            .get_image_data(
-                0.0,
-                0.0,
-                self.canvas.width().into(),
-                self.canvas.height().into(),
-            )
-            .expect("failed to retrieve image data")
-            .dyn_into::<ImageData>()
-            .expect("Failed to cast to ImageData");
+        let image_data = self.image_data();
 
         ctx.begin_path();
         ctx.set_stroke_style(&JsValue::from_str("#FFFFFF"));
@@ -131,6 +100,60 @@ impl Canvas {
         image_data
     }
 
+    /// Scaled down copy of the canvas as png data url.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn thumbnail(&self, width: u32) -> Option<String> {
+        let height = (f64::from(width) * f64::from(self.canvas.height())
+            / f64::from(self.canvas.width()))
+        .round() as u32;
+        let thumbnail = seed::document()
+            .create_element("canvas")
+            .ok()?
+            .dyn_into::<HtmlCanvasElement>()
+            .ok()?;
+        thumbnail.set_width(width);
+        thumbnail.set_height(height);
+        seed::canvas_context_2d(&thumbnail)
+            .draw_image_with_html_canvas_element_and_dw_and_dh(
+                &self.canvas,
+                0.0,
+                0.0,
+                width.into(),
+                height.into(),
+            )
+            .ok()?;
+        thumbnail.to_data_url().ok()
+    }
+
+    pub fn image_data(&self) -> ImageData {
+        seed::canvas_context_2d(&self.canvas)
+            .get_image_data(
+                0.0,
+                0.0,
+                self.canvas.width().into(),
+                self.canvas.height().into(),
+            )
+            .expect("failed to retrieve image data")
+            .dyn_into::<ImageData>()
+            .expect("Failed to cast to ImageData")
+    }
+
+    /// Draw `image_data` moved by `dx`, `dy`, uncovered parts are filled with the background.
+    pub fn draw_shifted(&self, image_data: &ImageData, dx: i32, dy: i32) {
+        let ctx = seed::canvas_context_2d(&self.canvas);
+        let background = self.coloring.background();
+        let background = rgb_to_html([background[0], background[1], background[2]]);
+        ctx.set_fill_style(&JsValue::from_str(background.as_str()));
+        ctx.fill_rect(
+            0.0,
+            0.0,
+            self.canvas.width().into(),
+            self.canvas.height().into(),
+        );
+        ctx.put_image_data(image_data, dx.into(), dy.into())
+            .expect("cannot draw image data");
+    }
+
     pub fn undraw(&self, image_data: &ImageData) {
         // log!(format!("undraw: ({},{}) width: {} height: {}", x_start,y_start, image_data.width(), image_data.height()));
         let ctx = seed::canvas_context_2d(&self.canvas);
@@ -156,88 +179,34 @@ impl Canvas {
         }
     }
 
-
-    #[allow(clippy::cast_precision_loss)]
-    fn iterations_as_hue_to_rgb(&self, iterations: u32) -> String {
-        Self::hue_to_rgb((iterations as f32).mul_add(HUE_RANGE / self.steps as f32, HUE_OFFSET) % 360.0)
-    }
-
-    #[allow(clippy::many_single_char_names, clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-    fn hue_to_rgb(hue: f32) -> String {
-        const TMP: f32 = 2.0 * DEFAULT_LIGHTNESS - 1.0;
-        const C: f32 = (1.0 - if TMP >= 0.0 { TMP } else { -TMP }) * DEFAULT_SATURATION;
-        const M: f32 = DEFAULT_LIGHTNESS - C / 2.0;
-        let x = C * (1.0 - f32::abs((hue / 60.0) % 2.0 - 1.0));
-
-        let (r, g, b) = match hue as u32 {
-            0..=59 => (C, x, 0.0),
-            60..=119 => (x, C, 0.0),
-            120..=179 => (0.0, C, x),
-            180..=239 => (0.0, x, C),
-            240..=299 => (x, 0.0, C),
-            _ => (C, 0.0, x)
-        };
-
-        let (r, g, b) = (
-            f32::floor((r + M) * 255.0).abs() as u32,
-            f32::floor((g + M) * 255.0).abs() as u32,
-            f32::floor((b + M) * 255.0).abs() as u32,
-        );
-
-        format!("#{:0>2X}{:0>2X}{:0>2X}", r % 0x100, g % 0x100, b % 0x100)
-    }
-
-    #[allow(clippy::many_single_char_names, clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-    fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> String {
-        // see: https://www.rapidtables.com/convert/color/hsl-to-rgb.html
-
-        assert!((0.0..=1.0).contains(&saturation));
-        assert!((0.0..=1.0).contains(&lightness));
-
-        let safe_hue = if hue >= 360.0 {
-            hue % 360.0
+    /// Part of the canvas inside the browser window as (`x_start`, `y_start`, `x_end`, `y_end`) in canvas
+    /// coordinates, the end coordinates are exclusive. None if all or nothing of the canvas is visible.
+    #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+    pub fn visible_area(&self) -> Option<(u32, u32, u32, u32)> {
+        let window = seed::window();
+        let view_width = window.inner_width().ok()?.as_f64()?;
+        let view_height = window.inner_height().ok()?.as_f64()?;
+        let (width, height) = (f64::from(self.canvas.width()), f64::from(self.canvas.height()));
+        let rect = self.canvas.get_bounding_client_rect();
+        if rect.width() <= 0.0 || rect.height() <= 0.0 {
+            return None;
+        }
+        let scale_x = width / rect.width();
+        let scale_y = height / rect.height();
+        let x_start = (-rect.left() * scale_x).clamp(0.0, width);
+        let y_start = (-rect.top() * scale_y).clamp(0.0, height);
+        let x_end = ((view_width - rect.left()) * scale_x).clamp(0.0, width);
+        let y_end = ((view_height - rect.top()) * scale_y).clamp(0.0, height);
+        let all = x_start <= 0.0 && y_start <= 0.0 && x_end >= width && y_end >= height;
+        if all || x_end <= x_start || y_end <= y_start {
+            None
         } else {
-            hue
-        };
-
-        let c = (1.0 - f32::abs(2.0 * lightness - 1.0)) * saturation;
-        let x = c * (1.0 - ((safe_hue / 60.0) % 2.0 - 1.0).abs());
-        let m = lightness - c / 2.0;
-        let (r, g, b) = match hue as u32 {
-            0..=59 => (c, x, 0.0),
-            60..=119 => (x, c, 0.0),
-            120..=179 => (0.0, c, x),
-            180..=239 => (0.0, x, c),
-            240..=299 => (x, 0.0, c),
-            300..=359 => (c, 0.0, x),
-            _ => {
-                panic!("invalid hue value");
-            }
-        };
-
-        let (r, g, b) = (
-            f32::floor((r + m) * 255.0).abs() as u32,
-            f32::floor((g + m) * 255.0).abs() as u32,
-            f32::floor((b + m) * 255.0).abs() as u32,
-        );
-
-        format!("#{:X}{:X}{:X}", r % 0x100, g % 0x100, b % 0x100)
-    }
-}
-
-#[cfg(test)]
-mod test {
-    use super::Canvas;
-    #[test]
-    fn test_iterations_as_hue_to_rgb() {
-        assert_eq!(Canvas::hue_to_rgb(0.0), "#FF0000");
-        assert_eq!(Canvas::hue_to_rgb(60.0), "#FFFF00");
-        assert_eq!(Canvas::hue_to_rgb(120.0), "#00FF00");
-        assert_eq!(Canvas::hue_to_rgb(180.0), "#00FFFF");
-        assert_eq!(Canvas::hue_to_rgb(240.0), "#0000FF");
-        assert_eq!(Canvas::hue_to_rgb(300.0), "#FF00FF");
-        assert_eq!(Canvas::hue_to_rgb(360.0), "#FF0000");
-        assert_eq!(Canvas::hue_to_rgb(340.0), "#FF0055");
-        // TODO: Tests for hsl_to_rgb
+            Some((
+                x_start as u32,
+                y_start as u32,
+                x_end.ceil() as u32,
+                y_end.ceil() as u32,
+            ))
+        }
     }
 }
@@ -0,0 +1,55 @@
+use seed::prelude::{js_sys, web_sys, JsCast};
+
+/// Source of timestamps in milliseconds for the statistics and the time slices of the renderer.
+/// The fractals only measure durations, the origin of the timestamps is arbitrary.
+pub trait Clock {
+    fn now(&self) -> f64;
+}
+
+/// Performance timer of the window, or of the worker scope when running in a web worker.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PerformanceClock;
+
+impl Clock for PerformanceClock {
+    fn now(&self) -> f64 {
+        web_sys::window()
+            .map_or_else(
+                || js_sys::global().unchecked_into::<web_sys::WorkerGlobalScope>().performance(),
+                |window| window.performance(),
+            )
+            .expect("performance should be available")
+            .now()
+    }
+}
+
+/// Monotonic clock of the operating system for native builds, `std::time::Instant` is not
+/// available in the browser.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+    origin: std::time::Instant,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl SystemClock {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            origin: std::time::Instant::now(),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Clock for SystemClock {
+    fn now(&self) -> f64 {
+        self.origin.elapsed().as_secs_f64() * 1000.0
+    }
+}
@@ -0,0 +1,140 @@
+use super::{
+    fractal::{IterationBuffer, Points, NO_ROOT},
+    palette::hsl_to_rgb,
+    polynomial::Polynomial,
+    Config, FractalType,
+};
+
+const DEFAULT_SATURATION: f32 = 1.0;
+const DEFAULT_LIGHTNESS: f32 = 0.5;
+
+// lightness factor applied per newton iteration, slow convergence gets darker
+const ROOT_SHADE: f32 = 0.95;
+
+enum ColorMode {
+    Iterations,
+    Roots(u32),
+}
+
+/// Maps the results of a render to RGBA colors, shared by the canvas and native renders
+/// so both produce the same pixels.
+pub struct Coloring {
+    steps: u32,
+    color_mode: ColorMode,
+    smooth: bool,
+    colors: Vec<[u8; 4]>,
+    background: [u8; 4],
+}
+
+impl Coloring {
+    /// Colors of the active fractal of `config`, points inside the set get `background`.
+    pub fn new(config: &Config, background: [u8; 4]) -> Self {
+        let steps = match config.active_config {
+            FractalType::JuliaSet => config.julia_set_cfg.max_iterations,
+            FractalType::Mandelbrot => config.mandelbrot_cfg.max_iterations,
+            FractalType::BurningShip => config.burning_ship_cfg.max_iterations,
+            FractalType::Newton => config.newton_cfg.max_iterations,
+        };
+        Self {
+            steps,
+            color_mode: match config.active_config {
+                FractalType::Newton => {
+                    #[allow(clippy::cast_possible_truncation)]
+                    let num_roots =
+                        Polynomial::new(&config.newton_cfg.coefficients).degree() as u32;
+                    ColorMode::Roots(num_roots)
+                }
+                _ => ColorMode::Iterations,
+            },
+            smooth: config.smooth_coloring,
+            colors: config.palette.build_table(steps),
+            background,
+        }
+    }
+
+    pub const fn background(&self) -> [u8; 4] {
+        self.background
+    }
+
+    /// RGBA colors of the points, 4 bytes per point.
+    pub fn to_rgba(&self, values: &[u32], smooth: &[f32], roots: &[u8]) -> Vec<u8> {
+        let mut buffer = Vec::with_capacity(values.len() * 4);
+        values
+            .iter()
+            .zip(smooth.iter())
+            .zip(roots.iter())
+            .for_each(|((value, smooth), root)| {
+                let color = match self.color_mode {
+                    ColorMode::Iterations => {
+                        if *value >= self.steps - 1 {
+                            self.background
+                        } else if self.smooth {
+                            self.smooth_to_rgb(*smooth)
+                        } else {
+                            self.colors[*value as usize]
+                        }
+                    }
+                    ColorMode::Roots(num_roots) => {
+                        if *root == NO_ROOT {
+                            self.background
+                        } else {
+                            Self::root_to_rgb(*root, *value, num_roots)
+                        }
+                    }
+                };
+                buffer.extend_from_slice(&color);
+            });
+        buffer
+    }
+
+    /// RGBA colors of the pixels of a tile, row by row, a point of a coarse pass fills its block.
+    pub fn tile_to_rgba(&self, points: &Points) -> Vec<u8> {
+        let tile = &points.tile;
+        let colors = self.to_rgba(&points.values, &points.smooth, &points.roots);
+        let columns = tile.width.div_ceil(tile.block) as usize;
+        let block = tile.block as usize;
+        let mut buffer = Vec::with_capacity(tile.len() * 4);
+        for row in 0..tile.height as usize {
+            for column in 0..tile.width as usize {
+                let offset = ((row / block) * columns + column / block) * 4;
+                buffer.extend_from_slice(&colors[offset..offset + 4]);
+            }
+        }
+        buffer
+    }
+
+    /// RGBA colors of a whole image, points not calculated yet get the background color.
+    pub fn image_to_rgba(&self, buffer: &IterationBuffer) -> Vec<u8> {
+        let mut colors = self.to_rgba(&buffer.values, &buffer.smooth, &buffer.roots);
+        for (idx, color) in colors.chunks_exact_mut(4).enumerate() {
+            if !buffer.is_stored(idx) {
+                color.copy_from_slice(&self.background);
+            }
+        }
+        colors
+    }
+
+    // blend the two neighbouring palette entries by the fractional part of the iteration count
+    #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+    fn smooth_to_rgb(&self, smooth: f32) -> [u8; 4] {
+        let lower = smooth.floor();
+        let idx = usize::min(lower as usize, self.colors.len() - 1);
+        let next = usize::min(idx + 1, self.colors.len() - 1);
+        let frac = smooth - lower;
+        let mut color = self.colors[idx];
+        color
+            .iter_mut()
+            .zip(self.colors[next].iter())
+            .for_each(|(from, to)| {
+                *from = (f32::from(*to) - f32::from(*from)).mul_add(frac, f32::from(*from)).round() as u8;
+            });
+        color
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn root_to_rgb(root: u8, iterations: u32, num_roots: u32) -> [u8; 4] {
+        let hue = f32::from(root) * 360.0 / num_roots as f32;
+        let lightness = DEFAULT_LIGHTNESS * ROOT_SHADE.powf(iterations as f32);
+        hsl_to_rgb(hue, DEFAULT_SATURATION, lightness)
+    }
+}
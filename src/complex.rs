@@ -1,9 +1,9 @@
 #![allow(clippy::missing_const_for_fn)]
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
-use std::ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign};
 
-#[derive(Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Complex {
     real: f64,
     imag: f64,
@@ -38,6 +38,43 @@ impl Complex {
     pub fn norm(&self) -> f64 {
         f64::sqrt(self.square_length())
     }
+    #[inline]
+    pub fn arg(&self) -> f64 {
+        self.imag.atan2(self.real)
+    }
+
+    pub fn from_polar(radius: f64, angle: f64) -> Self {
+        Self {
+            real: radius * angle.cos(),
+            imag: radius * angle.sin(),
+        }
+    }
+
+    pub fn powi(&self, exp: u32) -> Self {
+        // square and multiply
+        let mut res = Self::new(1.0, 0.0);
+        let mut base = *self;
+        let mut exp = exp;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                res *= base;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base *= base;
+            }
+        }
+        res
+    }
+
+    pub fn powf(&self, exp: f64) -> Self {
+        // principal value, branch cut along the negative real axis
+        if self.real == 0.0 && self.imag == 0.0 {
+            *self
+        } else {
+            Self::from_polar(self.norm().powf(exp), self.arg() * exp)
+        }
+    }
 }
 
 impl Add for Complex {
@@ -81,7 +118,7 @@ impl Mul for Complex {
     type Output = Self;
     fn mul(self, other: Self) -> Self::Output {
         Self {
-            real: self.real * other.real - self.imag * other.imag,
+            real: self.real.mul_add(other.real, -(self.imag * other.imag)),
             imag: self.real.mul_add(other.imag, self.imag * other.real),
         }
     }
@@ -89,15 +126,74 @@ impl Mul for Complex {
 
 impl MulAssign for Complex {
     fn mul_assign(&mut self, other: Self) {
-        let real = self.real * other.real - self.imag * other.imag;
+        let real = self.real.mul_add(other.real, -(self.imag * other.imag));
         let imag = self.real.mul_add(other.imag, self.imag * other.real);
         self.real = real;
         self.imag = imag;
     }
 }
 
+impl Div for Complex {
+    type Output = Self;
+    fn div(self, other: Self) -> Self::Output {
+        let denom = other.square_length();
+        Self {
+            real: self.real.mul_add(other.real, self.imag * other.imag) / denom,
+            imag: self.imag.mul_add(other.real, -self.real * other.imag) / denom,
+        }
+    }
+}
+
+impl DivAssign for Complex {
+    fn div_assign(&mut self, other: Self) {
+        let denom = other.square_length();
+        let real = self.real.mul_add(other.real, self.imag * other.imag) / denom;
+        let imag = self.imag.mul_add(other.real, -self.real * other.imag) / denom;
+        self.real = real;
+        self.imag = imag;
+    }
+}
+
 impl Display for Complex {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "({}+{}i)", self.real, self.imag)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::Complex;
+
+    fn assert_close(a: Complex, b: Complex) {
+        assert!((a - b).norm() < 1e-10, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_powi() {
+        let x = Complex::new(0.3, -1.2);
+        assert_close(x.powi(0), Complex::new(1.0, 0.0));
+        assert_close(x.powi(1), x);
+        assert_close(x.powi(2), x * x);
+        assert_close(x.powi(5), x * x * x * x * x);
+    }
+
+    #[test]
+    fn test_div() {
+        let x = Complex::new(0.3, -1.2);
+        let y = Complex::new(-2.0, 0.5);
+        assert_close((x * y) / y, x);
+        assert_close(Complex::new(1.0, 0.0) / Complex::new(0.0, 1.0), Complex::new(0.0, -1.0));
+        let mut z = x * y;
+        z /= x;
+        assert_close(z, y);
+    }
+
+    #[test]
+    fn test_powf() {
+        let x = Complex::new(-0.7, 0.4);
+        assert_close(x.powf(2.0), x * x);
+        assert_close(x.powf(3.0), x.powi(3));
+        assert_close(x.powf(0.5) * x.powf(0.5), x);
+        assert_close(Complex::new(0.0, 0.0).powf(2.5), Complex::new(0.0, 0.0));
+    }
+}
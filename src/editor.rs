@@ -0,0 +1,196 @@
+use super::{
+    bigfloat::BigComplex,
+    complex::Complex,
+    polynomial::format_coefficients,
+    util::format_coordinate,
+    validation::{
+        self, Errors, Inputs, COEFFICIENTS, C_IMAG, C_REAL, EXPONENT, ITERATIONS, MAX_IMAG,
+        MAX_REAL, MIN_IMAG, MIN_REAL,
+    },
+    Config, FractalType,
+};
+
+/// State of the parameter editor, the raw input values and their validation errors.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Editor {
+    inputs: Inputs,
+    errors: Errors,
+}
+
+impl Editor {
+    /// Editor holding the parameters and area of `fractal_type` in `config`.
+    #[must_use]
+    pub fn new(config: &Config, fractal_type: FractalType) -> Self {
+        let mut editor = Self::default();
+        editor.load_params(config, fractal_type);
+        let (origin, min, max) = match fractal_type {
+            FractalType::JuliaSet => {
+                let cfg = &config.julia_set_cfg;
+                (cfg.origin.clone(), cfg.x_min, cfg.x_max)
+            }
+            FractalType::Mandelbrot => {
+                let cfg = &config.mandelbrot_cfg;
+                (cfg.origin.clone(), cfg.c_min, cfg.c_max)
+            }
+            FractalType::BurningShip => (
+                BigComplex::default(),
+                config.burning_ship_cfg.c_min,
+                config.burning_ship_cfg.c_max,
+            ),
+            FractalType::Newton => (
+                BigComplex::default(),
+                config.newton_cfg.x_min,
+                config.newton_cfg.x_max,
+            ),
+        };
+        editor.load_area(&origin, min, max);
+        editor
+    }
+
+    #[must_use]
+    pub fn value(&self, field: &str) -> &str {
+        self.inputs.get(field).map_or("", String::as_str)
+    }
+
+    #[must_use]
+    pub fn error(&self, field: &str) -> Option<&str> {
+        self.errors.get(field).map(String::as_str)
+    }
+
+    #[must_use]
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    /// Update a field, a previous error is dropped until the next save.
+    pub fn set(&mut self, field: &'static str, value: String) {
+        self.errors.remove(field);
+        self.inputs.insert(field, value);
+    }
+
+    /// Replace the parameter fields, the area is kept.
+    pub fn load_params(&mut self, config: &Config, fractal_type: FractalType) {
+        match fractal_type {
+            FractalType::JuliaSet => {
+                let cfg = &config.julia_set_cfg;
+                self.set(ITERATIONS, cfg.max_iterations.to_string());
+                self.set(C_REAL, cfg.c.real().to_string());
+                self.set(C_IMAG, cfg.c.imag().to_string());
+                self.set(EXPONENT, cfg.exponent.to_string());
+            }
+            FractalType::Mandelbrot => {
+                let cfg = &config.mandelbrot_cfg;
+                self.set(ITERATIONS, cfg.max_iterations.to_string());
+                self.set(EXPONENT, cfg.exponent.to_string());
+            }
+            FractalType::BurningShip => {
+                self.set(ITERATIONS, config.burning_ship_cfg.max_iterations.to_string());
+            }
+            FractalType::Newton => {
+                let cfg = &config.newton_cfg;
+                self.set(ITERATIONS, cfg.max_iterations.to_string());
+                self.set(COEFFICIENTS, format_coefficients(&cfg.coefficients));
+            }
+        }
+    }
+
+    /// Replace the area fields by the corners `min` and `max` relative to `origin`,
+    /// deep zooms show all the decimal places telling the pixels apart.
+    pub fn load_area(&mut self, origin: &BigComplex, min: Complex, max: Complex) {
+        let width = f64::min(max.real() - min.real(), max.imag() - min.imag());
+        self.set(MAX_REAL, format_coordinate(origin.real(), max.real(), width));
+        self.set(MIN_REAL, format_coordinate(origin.real(), min.real(), width));
+        self.set(MAX_IMAG, format_coordinate(origin.imag(), max.imag(), width));
+        self.set(MIN_IMAG, format_coordinate(origin.imag(), min.imag(), width));
+    }
+
+    /// Validate the fields of `fractal_type` and apply them to `config`.
+    /// On failure `config` is unchanged and the errors are kept for display.
+    pub fn save(&mut self, fractal_type: FractalType, config: &mut Config) -> bool {
+        match validation::validate(fractal_type, &self.inputs, config) {
+            Ok(()) => {
+                self.errors.clear();
+                true
+            }
+            Err(errors) => {
+                self.errors = errors;
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Editor;
+    use crate::{
+        bigfloat::BigComplex,
+        complex::Complex,
+        validation::{COEFFICIENTS, ITERATIONS, MAX_IMAG, MAX_REAL, MIN_IMAG, MIN_REAL},
+        Config, FractalType,
+    };
+
+    #[test]
+    fn test_load() {
+        let config = Config::default();
+        let editor = Editor::new(&config, FractalType::Newton);
+        assert_eq!(
+            editor.value(ITERATIONS),
+            config.newton_cfg.max_iterations.to_string()
+        );
+        assert_eq!(
+            editor.value(MIN_REAL),
+            config.newton_cfg.x_min.real().to_string()
+        );
+        assert_eq!(editor.value(COEFFICIENTS), "1, 0, 0, -1");
+    }
+
+    #[test]
+    fn test_save() {
+        let mut config = Config::default();
+        let mut editor = Editor::new(&config, FractalType::Mandelbrot);
+        editor.set(ITERATIONS, "1234".to_string());
+        editor.load_area(
+            &BigComplex::default(),
+            Complex::new(-1.0, -0.5),
+            Complex::new(0.0, 0.5),
+        );
+        assert!(editor.save(FractalType::Mandelbrot, &mut config));
+        assert_eq!(config.mandelbrot_cfg.max_iterations, 1234);
+        assert_eq!(config.mandelbrot_cfg.c_min, Complex::new(-1.0, -0.5));
+        assert_eq!(config.mandelbrot_cfg.c_max, Complex::new(0.0, 0.5));
+    }
+
+    #[test]
+    fn test_save_invalid() {
+        let mut config = Config::default();
+        let mut editor = Editor::new(&config, FractalType::JuliaSet);
+        editor.set(MAX_REAL, "-10".to_string());
+        assert!(!editor.save(FractalType::JuliaSet, &mut config));
+        assert!(editor.error(MIN_REAL).is_some());
+        assert_eq!(config.julia_set_cfg, Config::default().julia_set_cfg);
+
+        // editing the field clears its error
+        editor.set(MIN_REAL, "-20".to_string());
+        assert!(!editor.has_errors());
+        assert!(editor.save(FractalType::JuliaSet, &mut config));
+    }
+
+    #[test]
+    fn test_deep_zoom() {
+        let mut config = Config::default();
+        let mut editor = Editor::new(&config, FractalType::Mandelbrot);
+        editor.set(MIN_REAL, "-1.74995768370609350360221450607069".to_string());
+        editor.set(MAX_REAL, "-1.74995768370609350360221450607065".to_string());
+        editor.set(MIN_IMAG, "-0.00000000000000000000000000000001".to_string());
+        editor.set(MAX_IMAG, "0.00000000000000000000000000000001".to_string());
+        assert!(editor.save(FractalType::Mandelbrot, &mut config));
+        assert!(config.mandelbrot_cfg.c_max.real() < 1e-30);
+
+        // the saved area shows the same coordinates
+        let editor = Editor::new(&config, FractalType::Mandelbrot);
+        assert_eq!(editor.value(MIN_REAL), "-1.74995768370609350360221450607069");
+        assert_eq!(editor.value(MAX_REAL), "-1.74995768370609350360221450607065");
+        assert_eq!(editor.value(MIN_IMAG), "-0.00000000000000000000000000000001");
+    }
+}
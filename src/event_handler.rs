@@ -1,212 +1,520 @@
 use std::cmp::Ordering;
 
 use super::{
+    bigfloat::BigComplex,
     canvas::Canvas,
+    clock::{Clock, PerformanceClock},
     complex::Complex,
-    fractal::Fractal,
-    julia_set::JuliaSet,
-    mandelbrot::Mandelbrot,
+    editor::Editor,
+    export::{config_from_png, copy_to_clipboard, download, encode_png, png_text},
+    fractal::{IterationBuffer, KnownPoints},
+    link,
+    palette::{html_to_rgba, Interpolation, Palette},
+    render::{self, create_fractal},
     stats::Stats,
-    util::{get_f64_from_input, get_u32_from_input, set_f64_on_input, set_u32_on_input},
-    FractalType, Model, MouseDrag, Msg, JULIA_DEFAULT_C, JULIA_DEFAULT_ITERATIONS, JULIA_DEFAULT_X,
-    MANDELBROT_DEFAULT_C_MAX, MANDELBROT_DEFAULT_C_MIN, MANDELBROT_DEFAULT_ITERATIONS, STORAGE_KEY,
+    scheduler::{Order, Scheduler, PASSES, TILE_SIZE},
+    util::{height_for_ratio, rebase_area, zoom_area},
+    Config, FractalType, Model, MouseDrag, Msg, Renderer, MAX_DURATION, BURNING_SHIP_DEFAULT_C_MAX, BURNING_SHIP_DEFAULT_C_MIN,
+    BURNING_SHIP_DEFAULT_ITERATIONS, JULIA_DEFAULT_C, JULIA_DEFAULT_EXPONENT,
+    JULIA_DEFAULT_ITERATIONS, JULIA_DEFAULT_X, MANDELBROT_DEFAULT_C_MAX, MANDELBROT_DEFAULT_C_MIN,
+    MANDELBROT_DEFAULT_EXPONENT, MANDELBROT_DEFAULT_ITERATIONS, NEWTON_DEFAULT_COEFFICIENTS,
+    NEWTON_DEFAULT_ITERATIONS, NEWTON_DEFAULT_X, BOOKMARKS_STORAGE_KEY, HISTORY_STORAGE_KEY, STORAGE_KEY, THUMBNAIL_WIDTH,
 };
-use seed::prelude::web_sys::{HtmlInputElement, HtmlSelectElement};
 #[allow(clippy::wildcard_imports)]
 use seed::{prelude::*, *};
 
 pub fn on_msg_start(model: &mut Model, orders: &mut impl Orders<Msg>) {
-    if let Some(canvas) = model.canvas.as_ref() {
-        canvas.clear_canvas(model);
-    } else {
-        let canvas = Canvas::new(model);
+    // a stopped render of an unchanged view continues with the missing tiles
+    let resumable = model
+        .scheduler
+        .as_ref()
+        .is_some_and(|scheduler| !scheduler.is_done());
+    if model.paused && resumable && model.render_key == render_key(model) {
+        if let Some(scheduler) = model.scheduler.as_mut() {
+            scheduler.resume();
+        }
+        if let Some(workers) = model.workers.as_mut() {
+            workers.start(&model.config, model.width, model.height);
+        }
+        model.paused = false;
+        continue_rendering(model, orders, true);
+        return;
+    }
+    start_rendering(model, orders, None);
+}
+
+// identifies the image in model.buffer, a render is only resumed for the same image
+fn render_key(model: &Model) -> String {
+    format!(
+        "{}x{} {}",
+        model.width,
+        model.height,
+        serde_json::to_string(&model.config).unwrap_or_default()
+    )
+}
+
+// starts a new render, points in known are copied instead of calculated
+fn start_rendering(model: &mut Model, orders: &mut impl Orders<Msg>, known: Option<KnownPoints>) {
+    // a running render already has a Msg::Draw scheduled
+    let was_paused = model.paused;
+    rebase_active_area(&mut model.config);
+    update_url(model);
+    // recreate canvas so color settings follow the active config
+    let canvas = Canvas::new(model);
+    // keep showing the shifted preview until it is overdrawn
+    if known.is_none() {
         canvas.clear_canvas(model);
-        model.canvas = Some(canvas);
     }
+    model.canvas = Some(canvas);
+    model.buffer = IterationBuffer::new(model.width, model.height);
 
     if model.config.view_stats {
-        model.stats = Some(Stats::new());
+        let mut stats = Stats::new(PerformanceClock.now());
+        stats.set_renderer(render::renderer(&model.config));
+        model.stats = Some(stats);
         model.stats_text = String::new();
     }
-    match model.config.active_config {
-        FractalType::JuliaSet => {
-            let mut fractal = JuliaSet::new(model);
-            model
-                .canvas
-                .as_ref()
-                .expect("unexpected missing canvas")
-                .draw_results(fractal.calculate(model.stats.as_mut()));
-
-            if let Some(stats) = model.stats.as_ref() {
-                model.stats_text = stats.format_stats();
-            }
+    // a panned view only calculates the uncovered tiles, a coarse preview would hide the known parts
+    let progressive = model.config.progressive && known.is_none();
+    let passes = if progressive { &PASSES[..] } else { &PASSES[PASSES.len() - 1..] };
+    let order = model
+        .canvas
+        .as_ref()
+        .and_then(Canvas::visible_area)
+        .map_or(Order::CenterOut, |(x_start, y_start, x_end, y_end)| Order::Visible {
+            x_start,
+            y_start,
+            x_end,
+            y_end,
+        });
+    let mut scheduler = Scheduler::new(model.width, model.height, TILE_SIZE, order, passes);
 
-            model.fractal = Some(Box::new(fractal));
-        }
-        FractalType::Mandelbrot => {
-            let mut fractal = Mandelbrot::new(model);
-            model
-                .canvas
-                .as_ref()
-                .expect("unexpected missing canvas")
-                .draw_results(fractal.calculate(model.stats.as_mut()));
-            if let Some(stats) = model.stats.as_ref() {
-                model.stats_text = stats.format_stats();
+    let fractal = create_fractal(
+        &model.config,
+        model.width,
+        model.height,
+        Box::new(PerformanceClock),
+    );
+    if let Some(known) = known {
+        // the tiles get their known points from the buffer
+        model.buffer.store_known(&known);
+        // tiles still covered by the moved image are copied instead of calculated
+        for tile in scheduler.tiles().to_vec() {
+            if known.tile_points(&tile).is_some() {
+                scheduler.complete(&tile);
             }
-            model.fractal = Some(Box::new(fractal));
         }
     }
+    model.fractal = Some(fractal);
+    model.scheduler = Some(scheduler);
+    model.render_key = render_key(model);
+    if let Some(workers) = model.workers.as_mut() {
+        // results of the previous view are dropped
+        workers.start(&model.config, model.width, model.height);
+    }
     model.paused = false;
+    continue_rendering(model, orders, was_paused);
+}
+
+// hands the scheduled tiles to the workers or calculates them on the main thread
+fn continue_rendering(model: &mut Model, orders: &mut impl Orders<Msg>, was_paused: bool) {
+    let scheduler = model.scheduler.as_mut().expect("unexpected missing scheduler");
+    if let Some(workers) = model.workers.as_mut() {
+        if let Err(err) = workers.dispatch(scheduler, &model.buffer) {
+            log!(err);
+            on_msg_worker_failed(model, orders);
+            return;
+        }
+        if scheduler.is_done() || !workers.is_busy() {
+            model.paused = true;
+        }
+    } else if was_paused {
+        orders.after_next_render(|_| Msg::Draw);
+    }
+}
 
-    orders.after_next_render(|_| Msg::Draw);
+pub fn on_msg_stop(model: &mut Model) {
+    model.paused = true;
+    if let Some(workers) = model.workers.as_mut() {
+        workers.cancel();
+    }
+    if let Some(scheduler) = model.scheduler.as_mut() {
+        scheduler.cancel();
+    }
 }
 
 pub fn on_msg_clear(model: &mut Model) {
     if !model.paused {
-        model.paused = true;
+        on_msg_stop(model);
     }
 
     if model.config.view_stats {
-        model.stats = Some(Stats::new());
+        model.stats = Some(Stats::new(PerformanceClock.now()));
         model.stats_text = String::new();
     }
 
     model.fractal = None;
-    if model.canvas.is_none() {
+    model.scheduler = None;
+    model.buffer.clear();
+    if let Some(canvas) = model.canvas.as_ref() {
+        canvas.clear_canvas(model);
+    } else {
         let canvas = Canvas::new(model);
         canvas.clear_canvas(model);
         model.canvas = Some(canvas);
-    } else {
-        model
-            .canvas
-            .as_ref()
-            .expect("unexpected empty canvas")
-            .clear_canvas(model);
     }
 }
 
-pub fn on_msg_type_changed(model: &mut Model) {
-    let selected = window()
-        .document()
-        .expect("document not found in window")
-        .get_element_by_id("type_select")
-        .expect("type_select not found")
-        .dyn_into::<HtmlSelectElement>()
-        .expect("type_select is not a HtmlSelectElement")
-        .value();
-
-    model.config.active_config = match selected.as_str() {
+pub fn on_msg_type_changed(model: &mut Model, value: &str) {
+    model.config.active_config = match value {
         "type_mandelbrot" => FractalType::Mandelbrot,
         "type_julia_set" => FractalType::JuliaSet,
+        "type_burning_ship" => FractalType::BurningShip,
+        "type_newton" => FractalType::Newton,
         _ => model.config.active_config,
     };
+    // stored results belong to the previous type
+    model.buffer.clear();
+}
+
+pub fn on_msg_recolor(model: &mut Model) {
+    if model.buffer.is_empty() {
+        return;
+    }
+
+    if model.buffer.width() != model.width || model.buffer.height() != model.height {
+        log!("stored results do not match the canvas size, not recoloring");
+        return;
+    }
+
+    update_url(model);
+    // recreate canvas to pick up the current color settings
+    let canvas = Canvas::new(model);
+    canvas.clear_canvas(model);
+    canvas.recolor(&model.buffer);
+    model.canvas = Some(canvas);
+}
+
+fn canvas_to_png(model: &Model) -> Result<Vec<u8>, String> {
+    let canvas = model.canvas.as_ref().ok_or("Nothing to export")?;
+    let text = png_text(&model.config)?;
+    let image_data = canvas.image_data();
+    encode_png(image_data.width(), image_data.height(), &image_data.data(), &text)
+}
+
+pub fn on_msg_export_png(model: &mut Model) {
+    match canvas_to_png(model) {
+        Ok(data) => {
+            let file_name = format!("{:?}.png", model.config.active_config).to_lowercase();
+            if let Err(err) = download(&data, "image/png", file_name.as_str()) {
+                log!(format!("failed to download png: {:?}", err));
+                model.status_text = "Failed to download image".to_string();
+            }
+        }
+        Err(err) => {
+            log!(err);
+            model.status_text = err;
+        }
+    }
+}
+
+pub fn on_msg_import_png(
+    model: &mut Model,
+    file: Option<web_sys::File>,
+    orders: &mut impl Orders<Msg>,
+) {
+    if let Some(file) = file {
+        model.status_text = format!("Reading {}...", file.name());
+        orders.perform_cmd(async move {
+            Msg::PngImported(
+                JsFuture::from(file.array_buffer())
+                    .await
+                    .map(|buffer| js_sys::Uint8Array::new(&buffer).to_vec())
+                    .map_err(|err| format!("failed to read file: {err:?}")),
+            )
+        });
+    }
+}
+
+pub fn on_msg_png_imported(
+    model: &mut Model,
+    result: Result<Vec<u8>, String>,
+    orders: &mut impl Orders<Msg>,
+) {
+    if model.edit_mode {
+        // the editor would overwrite the imported settings when it is saved
+        model.status_text = "Close the editor to import the settings of an image".to_string();
+        return;
+    }
+    match result.and_then(|data| config_from_png(&data)) {
+        Ok(config) => {
+            model.status_text = "Restored the settings of the image".to_string();
+            // record the previous view of the imported type
+            model.config.active_config = config.active_config;
+            model.history.record(&model.config);
+            model.config = config;
+            apply_restored_view(model, orders);
+        }
+        Err(err) => {
+            log!(err);
+            model.status_text = err;
+        }
+    }
+}
+
+pub fn on_msg_copy_to_clipboard(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    // the clipboard write has to start while the click is handled
+    match canvas_to_png(model).and_then(|data| copy_to_clipboard(&data)) {
+        Ok(write) => {
+            model.status_text = "Copying image to clipboard...".to_string();
+            orders.perform_cmd(async move { Msg::ClipboardResult(write.await) });
+        }
+        Err(err) => {
+            log!(err);
+            model.status_text = err;
+        }
+    }
+}
+
+pub fn on_msg_clipboard_result(model: &mut Model, result: Result<(), String>) {
+    model.status_text = match result {
+        Ok(()) => "Image copied to clipboard".to_string(),
+        Err(err) => {
+            log!(format!("failed to copy to clipboard: {}", err));
+            err
+        }
+    };
 }
 
-pub fn on_msg_stats_changed(model: &mut Model) {
-    let stats_cb = window()
-        .document()
-        .expect("document not found")
-        .get_element_by_id("stats_cb")
-        .expect("stats checkbox not found")
-        .dyn_into::<HtmlInputElement>()
-        .expect("Failed to cast to HtmlInputElement");
-    model.config.view_stats = stats_cb.checked();
+pub fn on_msg_stats_changed(model: &mut Model, checked: bool) {
+    model.config.view_stats = checked;
     LocalStorage::insert(STORAGE_KEY, &model.config).expect("save data to LocalStorage");
     if model.config.view_stats {
-        model.stats = Some(Stats::new());
-        model.stats_text = String::new();
+        model.stats = Some(Stats::new(PerformanceClock.now()));
     } else {
         model.stats = None;
-        model.stats_text = String::new();
     }
+    model.stats_text = String::new();
 }
 
-pub fn on_msg_draw(model: &mut Model, orders: &mut impl Orders<Msg>) {
-    if !model.paused {
-        let fractal = model.fractal.as_mut().expect("unexpected missing fractal");
-        model
-            .canvas
-            .as_ref()
-            .expect("unexpected missing canvas")
-            .draw_results(fractal.calculate(model.stats.as_mut()));
-        if let Some(stats) = model.stats.as_ref() {
-            model.stats_text = stats.format_stats();
+pub fn on_msg_smooth_changed(model: &mut Model, checked: bool) {
+    model.config.smooth_coloring = checked;
+    LocalStorage::insert(STORAGE_KEY, &model.config).expect("save data to LocalStorage");
+    on_msg_recolor(model);
+}
+
+pub fn on_msg_progressive_changed(model: &mut Model, checked: bool) {
+    model.config.progressive = checked;
+    LocalStorage::insert(STORAGE_KEY, &model.config).expect("save data to LocalStorage");
+}
+
+pub fn on_msg_renderer_changed(model: &mut Model, value: &str) {
+    model.config.renderer = match value {
+        "renderer_subdivision" => Renderer::Subdivision,
+        _ => Renderer::Scanline,
+    };
+    LocalStorage::insert(STORAGE_KEY, &model.config).expect("save data to LocalStorage");
+}
+
+pub fn on_msg_cardioid_check_changed(model: &mut Model, checked: bool) {
+    model.config.cardioid_check = checked;
+    LocalStorage::insert(STORAGE_KEY, &model.config).expect("save data to LocalStorage");
+}
+
+pub fn on_msg_periodicity_check_changed(model: &mut Model, checked: bool) {
+    model.config.periodicity_check = checked;
+    LocalStorage::insert(STORAGE_KEY, &model.config).expect("save data to LocalStorage");
+}
+
+pub fn on_msg_zoom_factor_changed(model: &mut Model, value: &str) {
+    match value.parse::<f64>() {
+        Ok(factor) if factor > 1.0 => {
+            model.config.zoom_factor = factor;
+            LocalStorage::insert(STORAGE_KEY, &model.config).expect("save data to LocalStorage");
         }
+        _ => log!(format!("invalid zoom factor: {}", value)),
+    }
+}
 
-        if fractal.is_done() {
-            model.paused = true;
+pub fn on_msg_edit_palette(model: &mut Model) {
+    model.palette_edit = Some(model.config.palette.clone());
+}
+
+pub fn on_msg_save_palette(model: &mut Model) {
+    if let Some(mut palette) = model.palette_edit.take() {
+        palette.sort();
+        if palette.is_valid() {
+            model.config.palette = palette;
+            LocalStorage::insert(STORAGE_KEY, &model.config).expect("save data to LocalStorage");
+            on_msg_recolor(model);
         } else {
-            orders.after_next_render(|_| Msg::Draw);
+            log!("invalid palette, not saving");
+            model.palette_edit = Some(palette);
         }
     }
 }
 
-pub fn on_msg_save_edit(model: &mut Model, orders: &mut impl Orders<Msg>) {
-    model.edit_mode = false;
-    let document = window().document().expect("document not found");
-    match model.config.active_config {
-        FractalType::JuliaSet => {
-            if let Some(value) = get_u32_from_input("julia_iterations") {
-                model.config.julia_set_cfg.max_iterations = value;
-            }
+pub fn on_msg_cancel_palette(model: &mut Model) {
+    model.palette_edit = None;
+}
 
-            if let Some(value) = get_f64_from_input("julia_max_real") {
-                model.config.julia_set_cfg.x_max.set_real(value);
-            }
+pub fn on_msg_reset_palette(model: &mut Model) {
+    if let Some(palette) = model.palette_edit.as_mut() {
+        *palette = Palette::default();
+    }
+}
 
-            if let Some(value) = get_f64_from_input("julia_min_real") {
-                model.config.julia_set_cfg.x_min.set_real(value);
-            }
+pub fn on_msg_add_palette_stop(model: &mut Model) {
+    if let Some(palette) = model.palette_edit.as_mut() {
+        palette.sort();
+        palette.add_stop();
+    }
+}
 
-            if let Some(value) = get_f64_from_input("julia_max_imag") {
-                model.config.julia_set_cfg.x_max.set_imag(value);
-            }
+pub fn on_msg_remove_palette_stop(model: &mut Model, idx: usize) {
+    if let Some(palette) = model.palette_edit.as_mut() {
+        palette.remove_stop(idx);
+    }
+}
 
-            if let Some(value) = get_f64_from_input("julia_min_imag") {
-                model.config.julia_set_cfg.x_min.set_imag(value);
-            }
+pub fn on_msg_palette_stop_color(model: &mut Model, idx: usize, value: &str) {
+    if let Some(stop) = model
+        .palette_edit
+        .as_mut()
+        .and_then(|palette| palette.stops.get_mut(idx))
+    {
+        let [r, g, b, _] = html_to_rgba(value);
+        stop.color = [r, g, b];
+    }
+}
 
-            if let Some(value) = get_f64_from_input("julia_c_real") {
-                model.config.julia_set_cfg.c.set_real(value);
-            }
+pub fn on_msg_palette_stop_position(model: &mut Model, idx: usize, value: &str) {
+    if let Some(stop) = model
+        .palette_edit
+        .as_mut()
+        .and_then(|palette| palette.stops.get_mut(idx))
+    {
+        match value.parse::<f32>() {
+            Ok(position) if (0.0..=1.0).contains(&position) => stop.position = position,
+            _ => log!(format!("invalid stop position: {}", value)),
+        }
+    }
+}
 
-            if let Some(value) = get_f64_from_input("julia_c_imag") {
-                model.config.julia_set_cfg.c.set_imag(value);
-            }
+pub fn on_msg_palette_interpolation(model: &mut Model, value: &str) {
+    if let Some(palette) = model.palette_edit.as_mut() {
+        palette.interpolation = match value {
+            "rgb" => Interpolation::Rgb,
+            "hsl" => Interpolation::Hsl,
+            _ => palette.interpolation,
+        };
+    }
+}
 
-            document
-                .get_element_by_id("julia_edit_cntr")
-                .expect("edit_cntr not found")
-                .set_class_name("edit_cntr_hidden");
-        }
-        FractalType::Mandelbrot => {
-            if let Some(value) = get_u32_from_input("mandelbrot_iterations") {
-                model.config.mandelbrot_cfg.max_iterations = value;
-            }
+pub fn on_msg_draw(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    if model.paused {
+        return;
+    }
+    let clock = PerformanceClock;
+    let start = clock.now();
+    let fractal = model.fractal.as_mut().expect("unexpected missing fractal");
+    let scheduler = model.scheduler.as_mut().expect("unexpected missing scheduler");
+    let canvas = model.canvas.as_ref().expect("unexpected missing canvas");
+    let mut pending = true;
+    #[allow(clippy::while_float)]
+    while clock.now() - start < MAX_DURATION {
+        let Some(tile) = scheduler.next() else {
+            pending = false;
+            break;
+        };
+        // coarser passes and a panned view left their points in the buffer
+        fractal.set_known_points(KnownPoints::from_buffer(&model.buffer, &tile));
+        let points = fractal.calculate(&tile, model.stats.as_mut());
+        model.buffer.store(&points);
+        canvas.draw_results(&points);
+        scheduler.complete(&tile);
+    }
+    if let Some(stats) = model.stats.as_ref() {
+        model.stats_text = stats.format_stats();
+    }
 
-            if let Some(value) = get_f64_from_input("mandelbrot_max_real") {
-                model.config.mandelbrot_cfg.c_max.set_real(value);
-            }
+    if pending && !scheduler.is_done() {
+        orders.after_next_render(|_| Msg::Draw);
+    } else {
+        model.paused = true;
+    }
+}
 
-            if let Some(value) = get_f64_from_input("mandelbrot_min_real") {
-                model.config.mandelbrot_cfg.c_min.set_real(value);
+pub fn on_msg_tile_done(model: &mut Model, data: &JsValue, orders: &mut impl Orders<Msg>) {
+    let Some(workers) = model.workers.as_mut() else {
+        return;
+    };
+    match workers.receive(data) {
+        Ok(Some((points, tile_stats))) => {
+            // tiles of different passes finish out of order, a coarse one must not hide a finer one
+            if !model.buffer.has_finer(&points.tile) {
+                model.buffer.store(&points);
+                if let Some(canvas) = model.canvas.as_ref() {
+                    canvas.draw_results(&points);
+                }
             }
-
-            if let Some(value) = get_f64_from_input("mandelbrot_max_imag") {
-                model.config.mandelbrot_cfg.c_max.set_imag(value);
+            if let Some(scheduler) = model.scheduler.as_mut() {
+                scheduler.complete(&points.tile);
             }
-
-            if let Some(value) = get_f64_from_input("mandelbrot_min_imag") {
-                model.config.mandelbrot_cfg.c_min.set_imag(value);
+            if let (Some(stats), Some(tile_stats)) = (model.stats.as_mut(), tile_stats.as_ref()) {
+                stats.merge(tile_stats, PerformanceClock.now());
+                model.stats_text = stats.format_stats();
             }
+        }
+        // a result of a cancelled render
+        Ok(None) => (),
+        Err(err) => {
+            log!(format!("web worker failed: {}", err));
+            on_msg_worker_failed(model, orders);
+            return;
+        }
+    }
+    if model.paused {
+        return;
+    }
+    if let Some(scheduler) = model.scheduler.as_mut() {
+        if let Err(err) = workers.dispatch(scheduler, &model.buffer) {
+            log!(err);
+            on_msg_worker_failed(model, orders);
+            return;
+        }
+        if scheduler.is_done() || !workers.is_busy() {
+            model.paused = true;
+        }
+    }
+}
 
-            document
-                .get_element_by_id("mandelbrot_edit_cntr")
-                .expect("edit_cntr not found")
-                .set_class_name("edit_cntr_hidden");
+// continues rendering on the main thread
+pub fn on_msg_worker_failed(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    if model.workers.take().is_none() {
+        return;
+    }
+    model.status_text = "Web workers failed, rendering on the main thread".to_string();
+    if !model.paused {
+        // tiles lost with the workers are handed out again
+        if let Some(scheduler) = model.scheduler.as_mut() {
+            scheduler.resume();
         }
+        orders.after_next_render(|_| Msg::Draw);
+    }
+}
+
+pub fn on_msg_save_edit(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    // invalid input keeps the editor open with the errors shown next to the fields
+    if !model.editor.save(model.config.active_config, &mut model.config) {
+        log!("invalid editor input, not saved");
+        return;
     }
+    model.edit_mode = false;
+
     LocalStorage::insert(STORAGE_KEY, &model.config).expect("save data to LocalStorage");
+    model.history.commit(&model.config);
+    LocalStorage::insert(HISTORY_STORAGE_KEY, &model.history).expect("save data to LocalStorage");
 
     adjust_height_to_ratio(model);
 
@@ -216,30 +524,18 @@ pub fn on_msg_save_edit(model: &mut Model, orders: &mut impl Orders<Msg>) {
 
 pub fn on_msg_cancel_edit(model: &mut Model) {
     model.edit_mode = false;
-    match model.config.active_config {
-        FractalType::JuliaSet => {
-            window()
-                .document()
-                .expect("document not found")
-                .get_element_by_id("julia_edit_cntr")
-                .expect("edit_cntr not found")
-                .set_class_name("edit_cntr_hidden");
-        }
-        FractalType::Mandelbrot => {
-            window()
-                .document()
-                .expect("document not found")
-                .get_element_by_id("mandelbrot_edit_cntr")
-                .expect("edit_cntr not found")
-                .set_class_name("edit_cntr_hidden");
-        }
-    }
+    // undo resets applied while editing
+    model.history.rollback(&mut model.config);
+}
+
+pub fn on_msg_input_changed(model: &mut Model, field: &'static str, value: String) {
+    model.editor.set(field, value);
 }
 
 pub fn on_msg_edit(model: &mut Model) {
     model.edit_mode = true;
-    set_editor_fields_params(model);
-    set_editor_fields_area(model);
+    model.history.begin(&model.config);
+    model.editor = Editor::new(&model.config, model.config.active_config);
 }
 
 pub fn on_msg_reset_params(model: &mut Model) {
@@ -247,12 +543,21 @@ pub fn on_msg_reset_params(model: &mut Model) {
         FractalType::JuliaSet => {
             model.config.julia_set_cfg.max_iterations = JULIA_DEFAULT_ITERATIONS;
             model.config.julia_set_cfg.c = Complex::new(JULIA_DEFAULT_C.0, JULIA_DEFAULT_C.1);
+            model.config.julia_set_cfg.exponent = JULIA_DEFAULT_EXPONENT;
         }
         FractalType::Mandelbrot => {
             model.config.mandelbrot_cfg.max_iterations = MANDELBROT_DEFAULT_ITERATIONS;
+            model.config.mandelbrot_cfg.exponent = MANDELBROT_DEFAULT_EXPONENT;
+        }
+        FractalType::BurningShip => {
+            model.config.burning_ship_cfg.max_iterations = BURNING_SHIP_DEFAULT_ITERATIONS;
+        }
+        FractalType::Newton => {
+            model.config.newton_cfg.max_iterations = NEWTON_DEFAULT_ITERATIONS;
+            model.config.newton_cfg.coefficients = NEWTON_DEFAULT_COEFFICIENTS.to_vec();
         }
     }
-    set_editor_fields_params(model);
+    model.editor.load_params(&model.config, model.config.active_config);
 }
 
 pub fn on_msg_reset_area(model: &mut Model) {
@@ -260,15 +565,28 @@ pub fn on_msg_reset_area(model: &mut Model) {
         FractalType::JuliaSet => {
             model.config.julia_set_cfg.x_max = Complex::new(JULIA_DEFAULT_X.0, JULIA_DEFAULT_X.1);
             model.config.julia_set_cfg.x_min = Complex::new(-JULIA_DEFAULT_X.0, -JULIA_DEFAULT_X.1);
+            model.config.julia_set_cfg.origin = BigComplex::default();
         }
         FractalType::Mandelbrot => {
             model.config.mandelbrot_cfg.c_max =
                 Complex::new(MANDELBROT_DEFAULT_C_MAX.0, MANDELBROT_DEFAULT_C_MAX.1);
             model.config.mandelbrot_cfg.c_min =
                 Complex::new(MANDELBROT_DEFAULT_C_MIN.0, MANDELBROT_DEFAULT_C_MIN.1);
+            model.config.mandelbrot_cfg.origin = BigComplex::default();
+        }
+        FractalType::BurningShip => {
+            model.config.burning_ship_cfg.c_max =
+                Complex::new(BURNING_SHIP_DEFAULT_C_MAX.0, BURNING_SHIP_DEFAULT_C_MAX.1);
+            model.config.burning_ship_cfg.c_min =
+                Complex::new(BURNING_SHIP_DEFAULT_C_MIN.0, BURNING_SHIP_DEFAULT_C_MIN.1);
+        }
+        FractalType::Newton => {
+            model.config.newton_cfg.x_max = Complex::new(NEWTON_DEFAULT_X.0, NEWTON_DEFAULT_X.1);
+            model.config.newton_cfg.x_min = Complex::new(-NEWTON_DEFAULT_X.0, -NEWTON_DEFAULT_X.1);
         }
     }
-    set_editor_fields_area(model);
+    let (min, max) = active_area(&model.config);
+    model.editor.load_area(&active_origin(&model.config), min, max);
 }
 
 pub fn on_msg_zoom_out_area(model: &mut Model) {
@@ -291,22 +609,47 @@ pub fn on_msg_zoom_out_area(model: &mut Model) {
             model.config.mandelbrot_cfg.c_max += increment;
             model.config.mandelbrot_cfg.c_min -= increment;
         }
+        FractalType::BurningShip => {
+            let increment = Complex::new(
+                (model.config.burning_ship_cfg.c_max.real() - model.config.burning_ship_cfg.c_min.real()).abs() / 2.0,
+                (model.config.burning_ship_cfg.c_max.imag() - model.config.burning_ship_cfg.c_min.imag()).abs() / 2.0
+            );
+
+            model.config.burning_ship_cfg.c_max += increment;
+            model.config.burning_ship_cfg.c_min -= increment;
+        }
+        FractalType::Newton => {
+            let increment = Complex::new(
+                (model.config.newton_cfg.x_max.real() - model.config.newton_cfg.x_min.real()).abs() / 2.0,
+                (model.config.newton_cfg.x_max.imag() - model.config.newton_cfg.x_min.imag()).abs() / 2.0
+            );
+
+            model.config.newton_cfg.x_max += increment;
+            model.config.newton_cfg.x_min -= increment;
+        }
     }
 
-    set_editor_fields_area(model);
+    let (min, max) = active_area(&model.config);
+    model.editor.load_area(&active_origin(&model.config), min, max);
 }
 
 pub fn on_msg_mouse_down(model: &mut Model, ev: &web_sys::MouseEvent) {
-    if let Some(canvas_coords) = model
-        .canvas
-        .as_ref()
-        .expect("unexpected missing canvas")
-        .viewport_to_canvas_coords(ev.client_x(), ev.client_y())
-    {
+    let canvas = model.canvas.as_ref().expect("unexpected missing canvas");
+    if let Some(canvas_coords) = canvas.viewport_to_canvas_coords(ev.client_x(), ev.client_y()) {
+        // outside of the editor dragging pans the image
+        let pan = !model.edit_mode;
+        // tiles finished while panning would be drawn over the moving image
+        let resume = pan && !model.paused;
+        if resume {
+            on_msg_stop(model);
+        }
+        let canvas = model.canvas.as_ref().expect("unexpected missing canvas");
         model.mouse_drag = Some(MouseDrag {
             start: canvas_coords,
             curr: canvas_coords,
-            image_data: None,
+            image_data: if pan { Some(canvas.image_data()) } else { None },
+            pan,
+            resume,
         });
     }
 }
@@ -319,6 +662,21 @@ pub fn on_msg_mouse_move(
     if let Some(mouse_drag) = model.mouse_drag.as_mut() {
         let canvas = model.canvas.as_ref().expect("unexpected missing canvas");
 
+        if mouse_drag.pan {
+            if let Some(canvas_coords) =
+                canvas.viewport_to_canvas_coords(ev.client_x(), ev.client_y())
+            {
+                mouse_drag.curr = canvas_coords;
+                let (dx, dy) = mouse_drag.offset();
+                if let Some(image_data) = mouse_drag.image_data.as_ref() {
+                    canvas.draw_shifted(image_data, dx, dy);
+                }
+            } else {
+                orders.after_next_render(|_| Msg::MouseUp(None));
+            }
+            return;
+        }
+
         if let Some(image_data) = mouse_drag.image_data.as_ref() {
             canvas.undraw(image_data);
         }
@@ -338,7 +696,16 @@ pub fn on_msg_mouse_move(
         }
     }
 }
-pub fn on_msg_mouse_up(model: &mut Model, ev: Option<web_sys::MouseEvent>) {
+pub fn on_msg_mouse_up(
+    model: &mut Model,
+    ev: Option<web_sys::MouseEvent>,
+    orders: &mut impl Orders<Msg>,
+) {
+    if model.mouse_drag.as_ref().is_some_and(|mouse_drag| mouse_drag.pan) {
+        finish_pan(model, ev, orders);
+        return;
+    }
+
     if let Some(mouse_drag) = model.mouse_drag.as_mut() {
         let canvas = model.canvas.as_ref().expect("unexpected missing canvas");
         if let Some(image_data) = mouse_drag.image_data.as_ref() {
@@ -374,145 +741,331 @@ pub fn on_msg_mouse_up(model: &mut Model, ev: Option<web_sys::MouseEvent>) {
             "setting new values, canvas coordinates: ({},{}), ({},{})",
             x_start, y_start, x_end, y_end
         ));
-        match model.config.active_config {
-            FractalType::JuliaSet => {
-                let x_scale = (model.config.julia_set_cfg.x_max.real()
-                    - model.config.julia_set_cfg.x_min.real())
-                    / f64::from(model.width);
-                set_f64_on_input(
-                    "julia_max_real",
-                    f64::from(x_end).mul_add(x_scale, model.config.julia_set_cfg.x_min.real()),
-                );
-                set_f64_on_input(
-                    "julia_min_real",
-                    f64::from(x_start).mul_add(x_scale, model.config.julia_set_cfg.x_min.real()),
-                );
-                let x_scale = (model.config.julia_set_cfg.x_max.imag()
-                    - model.config.julia_set_cfg.x_min.imag())
-                    / f64::from(model.height);
-                set_f64_on_input(
-                    "julia_max_imag",
-                    f64::from(y_end).mul_add(x_scale, model.config.julia_set_cfg.x_min.imag()),
-                );
-                set_f64_on_input(
-                    "julia_min_imag",
-                    f64::from(y_start).mul_add(x_scale, model.config.julia_set_cfg.x_min.imag()),
-                );
-            }
-            FractalType::Mandelbrot => {
-                let x_scale = (model.config.mandelbrot_cfg.c_max.real()
-                    - model.config.mandelbrot_cfg.c_min.real())
-                    / f64::from(model.width);
-                set_f64_on_input(
-                    "mandelbrot_max_real",
-                    f64::from(x_end).mul_add(x_scale, model.config.mandelbrot_cfg.c_min.real()),
-                );
-                set_f64_on_input(
-                    "mandelbrot_min_real",
-                    f64::from(x_start).mul_add(x_scale, model.config.mandelbrot_cfg.c_min.real()),
-                );
-                let x_scale = (model.config.mandelbrot_cfg.c_max.imag()
-                    - model.config.mandelbrot_cfg.c_min.imag())
-                    / f64::from(model.height);
-                set_f64_on_input(
-                    "mandelbrot_max_imag",
-                    f64::from(y_end).mul_add(x_scale, model.config.mandelbrot_cfg.c_min.imag()),
-                );
-                set_f64_on_input(
-                    "mandelbrot_min_imag",
-                    f64::from(y_start).mul_add(x_scale, model.config.mandelbrot_cfg.c_min.imag()),
-                );
-            }
-        }
+        let (min, max) = active_area(&model.config);
+        let x_scale = (max.real() - min.real()) / f64::from(model.width);
+        let y_scale = (max.imag() - min.imag()) / f64::from(model.height);
+        model.editor.load_area(
+            &active_origin(&model.config),
+            Complex::new(
+                f64::from(x_start).mul_add(x_scale, min.real()),
+                f64::from(y_start).mul_add(y_scale, min.imag()),
+            ),
+            Complex::new(
+                f64::from(x_end).mul_add(x_scale, min.real()),
+                f64::from(y_end).mul_add(y_scale, min.imag()),
+            ),
+        );
 
         mouse_drag.image_data = None;
         model.mouse_drag = None;
     }
 }
 
-fn adjust_height_to_ratio(model: &mut Model) {
-    let dim = match model.config.active_config {
-        FractalType::JuliaSet => {
-            model.config.julia_set_cfg.x_max - model.config.julia_set_cfg.x_min
+pub fn on_msg_wheel(model: &mut Model, ev: &web_sys::WheelEvent, orders: &mut impl Orders<Msg>) {
+    if model.edit_mode || ev.delta_y() == 0.0 {
+        return;
+    }
+
+    let canvas_coords = model
+        .canvas
+        .as_ref()
+        .and_then(|canvas| canvas.viewport_to_canvas_coords(ev.client_x(), ev.client_y()));
+
+    if let Some((x, y)) = canvas_coords {
+        // scrolling up zooms in
+        let factor = if ev.delta_y() < 0.0 {
+            1.0 / model.config.zoom_factor
+        } else {
+            model.config.zoom_factor
+        };
+        model.history.record(&model.config);
+        let (width, height) = (f64::from(model.width), f64::from(model.height));
+        let (min, max) = active_area_mut(&mut model.config);
+        let center = Complex::new(
+            f64::from(x).mul_add((max.real() - min.real()) / width, min.real()),
+            f64::from(y).mul_add((max.imag() - min.imag()) / height, min.imag()),
+        );
+        let (new_min, new_max) = zoom_area(*min, *max, center, factor);
+        *min = new_min;
+        *max = new_max;
+
+        LocalStorage::insert(STORAGE_KEY, &model.config).expect("save data to LocalStorage");
+        LocalStorage::insert(HISTORY_STORAGE_KEY, &model.history)
+            .expect("save data to LocalStorage");
+        on_msg_start(model, orders);
+    }
+}
+
+pub fn on_msg_back(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    if !model.edit_mode && model.history.back(&mut model.config) {
+        apply_restored_view(model, orders);
+    }
+}
+
+pub fn on_msg_forward(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    if !model.edit_mode && model.history.forward(&mut model.config) {
+        apply_restored_view(model, orders);
+    }
+}
+
+pub fn on_msg_key_down(
+    model: &mut Model,
+    ev: &web_sys::KeyboardEvent,
+    orders: &mut impl Orders<Msg>,
+) {
+    // leave the shortcuts to the inputs while an editor is open
+    if model.edit_mode || model.palette_edit.is_some() || !(ev.ctrl_key() || ev.meta_key()) {
+        return;
+    }
+    // the key events of all elements arrive here, form fields keep their native undo
+    let in_form_field = ev
+        .target()
+        .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+        .is_some_and(|element| {
+            matches!(element.tag_name().to_lowercase().as_str(), "input" | "textarea" | "select")
+        });
+    if in_form_field {
+        return;
+    }
+    match ev.key().to_lowercase().as_str() {
+        "z" if ev.shift_key() => {
+            ev.prevent_default();
+            on_msg_forward(model, orders);
         }
-        FractalType::Mandelbrot => {
-            model.config.mandelbrot_cfg.c_max - model.config.mandelbrot_cfg.c_min
+        "z" => {
+            ev.prevent_default();
+            on_msg_back(model, orders);
+        }
+        "y" => {
+            ev.prevent_default();
+            on_msg_forward(model, orders);
         }
+        _ => (),
+    }
+}
+
+pub fn on_msg_add_bookmark(model: &mut Model) {
+    let name = model.bookmark_name.trim();
+    let name = if name.is_empty() {
+        format!("{:?} {}", model.config.active_config, model.bookmarks.entries().len() + 1)
+    } else {
+        name.to_string()
+    };
+    let thumbnail = if model.buffer.is_empty() {
+        None
+    } else {
+        model
+            .canvas
+            .as_ref()
+            .and_then(|canvas| canvas.thumbnail(THUMBNAIL_WIDTH))
     };
-    model.height = (f64::from(model.width) * dim.imag() / dim.real()) as u32;
+    model
+        .bookmarks
+        .add(name.as_str(), &model.config, thumbnail.unwrap_or_default());
+    model.bookmark_name = String::new();
+    save_bookmarks(model);
 }
 
-fn set_editor_fields_params(model: &Model) {
-    match model.config.active_config {
-        FractalType::JuliaSet => {
-            set_u32_on_input(
-                "julia_iterations",
-                model.config.julia_set_cfg.max_iterations,
-            );
-            set_f64_on_input("julia_c_real", model.config.julia_set_cfg.c.real());
-            set_f64_on_input("julia_c_imag", model.config.julia_set_cfg.c.imag());
+pub fn on_msg_restore_bookmark(model: &mut Model, idx: usize, orders: &mut impl Orders<Msg>) {
+    if model.edit_mode {
+        return;
+    }
+    let mut config = model.config.clone();
+    if model.bookmarks.restore(idx, &mut config) {
+        // record the previous view of the bookmarked type
+        model.config.active_config = config.active_config;
+        model.history.record(&model.config);
+        model.config = config;
+        apply_restored_view(model, orders);
+    }
+}
+
+pub fn on_msg_rename_bookmark(model: &mut Model, idx: usize, name: &str) {
+    model.bookmarks.rename(idx, name.trim());
+    save_bookmarks(model);
+}
+
+pub fn on_msg_delete_bookmark(model: &mut Model, idx: usize) {
+    model.bookmarks.remove(idx);
+    save_bookmarks(model);
+}
 
-            window()
-                .document()
-                .expect("document not found")
-                .get_element_by_id("julia_edit_cntr")
-                .expect("edit_cntr not found")
-                .set_class_name("edit_cntr_visible");
+pub fn on_msg_export_bookmarks(model: &mut Model) {
+    match model.bookmarks.to_json() {
+        Ok(json) => {
+            if let Err(err) = download(json.as_bytes(), "application/json", "seed-fractals-bookmarks.json") {
+                log!(format!("failed to download bookmarks: {:?}", err));
+                model.status_text = "Failed to download bookmarks".to_string();
+            }
         }
-        FractalType::Mandelbrot => {
-            set_u32_on_input(
-                "mandelbrot_iterations",
-                model.config.mandelbrot_cfg.max_iterations,
+        Err(err) => {
+            log!(err);
+            model.status_text = err;
+        }
+    }
+}
+
+pub fn on_msg_import_bookmarks(
+    model: &mut Model,
+    file: Option<web_sys::File>,
+    orders: &mut impl Orders<Msg>,
+) {
+    if let Some(file) = file {
+        model.status_text = format!("Importing bookmarks from {}...", file.name());
+        orders.perform_cmd(async move {
+            Msg::BookmarksImported(
+                JsFuture::from(file.text())
+                    .await
+                    .map_err(|err| format!("failed to read file: {err:?}"))
+                    .and_then(|text| text.as_string().ok_or_else(|| "file is not a text file".to_string())),
+            )
+        });
+    }
+}
+
+pub fn on_msg_bookmarks_imported(model: &mut Model, result: Result<String, String>) {
+    match result.and_then(|json| model.bookmarks.import(json.as_str())) {
+        Ok((count, 0)) => {
+            model.status_text = format!("Imported {count} bookmarks");
+            save_bookmarks(model);
+        }
+        Ok((count, skipped)) => {
+            model.status_text = format!(
+                "Imported {count} bookmarks, skipped {skipped} with invalid settings"
             );
-            window()
-                .document()
-                .expect("document not found")
-                .get_element_by_id("mandelbrot_edit_cntr")
-                .expect("edit_cntr not found")
-                .set_class_name("edit_cntr_visible");
+            save_bookmarks(model);
+        }
+        Err(err) => {
+            log!(err);
+            model.status_text = err;
         }
     }
 }
 
-fn set_editor_fields_area(model: &Model) {
-    match model.config.active_config {
+fn save_bookmarks(model: &Model) {
+    if let Err(err) = LocalStorage::insert(BOOKMARKS_STORAGE_KEY, &model.bookmarks) {
+        log!(format!("failed to save bookmarks: {:?}", err));
+    }
+}
+
+fn apply_restored_view(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    LocalStorage::insert(STORAGE_KEY, &model.config).expect("save data to LocalStorage");
+    LocalStorage::insert(HISTORY_STORAGE_KEY, &model.history).expect("save data to LocalStorage");
+    adjust_height_to_ratio(model);
+    start_rendering(model, orders, None);
+}
+
+// replace the url hash with a deep link to the current view, without adding a browser history entry
+fn update_url(model: &Model) {
+    let hash = format!("#{}", link::encode(&model.config));
+    if let Err(err) = window()
+        .history()
+        .and_then(|history| history.replace_state_with_url(&JsValue::NULL, "", Some(hash.as_str())))
+    {
+        log!(format!("failed to update url: {:?}", err));
+    }
+}
+
+// returns the (min, max) corners of the area of the active fractal
+pub const fn active_area(config: &Config) -> (Complex, Complex) {
+    match config.active_config {
+        FractalType::JuliaSet => (config.julia_set_cfg.x_min, config.julia_set_cfg.x_max),
+        FractalType::Mandelbrot => (config.mandelbrot_cfg.c_min, config.mandelbrot_cfg.c_max),
+        FractalType::BurningShip => (config.burning_ship_cfg.c_min, config.burning_ship_cfg.c_max),
+        FractalType::Newton => (config.newton_cfg.x_min, config.newton_cfg.x_max),
+    }
+}
+
+// the arbitrary precision origin the area of the active fractal is relative to
+pub fn active_origin(config: &Config) -> BigComplex {
+    match config.active_config {
+        FractalType::JuliaSet => config.julia_set_cfg.origin.clone(),
+        FractalType::Mandelbrot => config.mandelbrot_cfg.origin.clone(),
+        FractalType::BurningShip | FractalType::Newton => BigComplex::default(),
+    }
+}
+
+// keeps the corners of deep zooms close to the origin, see util::rebase_area
+fn rebase_active_area(config: &mut Config) {
+    match config.active_config {
         FractalType::JuliaSet => {
-            set_f64_on_input("julia_max_real", model.config.julia_set_cfg.x_max.real());
-            set_f64_on_input("julia_min_real", model.config.julia_set_cfg.x_min.real());
-            set_f64_on_input("julia_max_imag", model.config.julia_set_cfg.x_max.imag());
-            set_f64_on_input("julia_min_imag", model.config.julia_set_cfg.x_min.imag());
-
-            window()
-                .document()
-                .expect("document not found")
-                .get_element_by_id("julia_edit_cntr")
-                .expect("edit_cntr not found")
-                .set_class_name("edit_cntr_visible");
+            let cfg = &mut config.julia_set_cfg;
+            rebase_area(&mut cfg.origin, &mut cfg.x_min, &mut cfg.x_max);
         }
         FractalType::Mandelbrot => {
-            set_f64_on_input(
-                "mandelbrot_max_real",
-                model.config.mandelbrot_cfg.c_max.real(),
-            );
-            set_f64_on_input(
-                "mandelbrot_min_real",
-                model.config.mandelbrot_cfg.c_min.real(),
-            );
-            set_f64_on_input(
-                "mandelbrot_max_imag",
-                model.config.mandelbrot_cfg.c_max.imag(),
-            );
-            set_f64_on_input(
-                "mandelbrot_min_imag",
-                model.config.mandelbrot_cfg.c_min.imag(),
-            );
+            let cfg = &mut config.mandelbrot_cfg;
+            rebase_area(&mut cfg.origin, &mut cfg.c_min, &mut cfg.c_max);
+        }
+        FractalType::BurningShip | FractalType::Newton => (),
+    }
+}
+
+pub const fn active_area_mut(config: &mut Config) -> (&mut Complex, &mut Complex) {
+    match config.active_config {
+        FractalType::JuliaSet => (&mut config.julia_set_cfg.x_min, &mut config.julia_set_cfg.x_max),
+        FractalType::Mandelbrot => (&mut config.mandelbrot_cfg.c_min, &mut config.mandelbrot_cfg.c_max),
+        FractalType::BurningShip => (
+            &mut config.burning_ship_cfg.c_min,
+            &mut config.burning_ship_cfg.c_max,
+        ),
+        FractalType::Newton => (&mut config.newton_cfg.x_min, &mut config.newton_cfg.x_max),
+    }
+}
+
+fn finish_pan(
+    model: &mut Model,
+    ev: Option<web_sys::MouseEvent>,
+    orders: &mut impl Orders<Msg>,
+) {
+    let Some(mut mouse_drag) = model.mouse_drag.take() else {
+        return;
+    };
+
+    if let Some(mouse_ev) = ev {
+        if let Some(canvas_coords) = model
+            .canvas
+            .as_ref()
+            .expect("unexpected missing canvas")
+            .viewport_to_canvas_coords(mouse_ev.client_x(), mouse_ev.client_y())
+        {
+            mouse_drag.curr = canvas_coords;
+        }
+    }
 
-            window()
-                .document()
-                .expect("document not found")
-                .get_element_by_id("mandelbrot_edit_cntr")
-                .expect("edit_cntr not found")
-                .set_class_name("edit_cntr_visible");
+    let (dx, dy) = mouse_drag.offset();
+    if dx == 0 && dy == 0 {
+        if mouse_drag.resume {
+            on_msg_start(model, orders);
         }
+        return;
     }
+
+    model.history.record(&model.config);
+    // the image moves with the mouse so the area moves the opposite way
+    let (width, height) = (f64::from(model.width), f64::from(model.height));
+    let (min, max) = active_area_mut(&mut model.config);
+    let shift = Complex::new(
+        -f64::from(dx) * (max.real() - min.real()) / width,
+        -f64::from(dy) * (max.imag() - min.imag()) / height,
+    );
+    *min += shift;
+    *max += shift;
+    LocalStorage::insert(STORAGE_KEY, &model.config).expect("save data to LocalStorage");
+    LocalStorage::insert(HISTORY_STORAGE_KEY, &model.history).expect("save data to LocalStorage");
+
+    let known = if model.buffer.width() == model.width && model.buffer.height() == model.height {
+        Some(KnownPoints::shifted(&model.buffer, dx, dy))
+    } else {
+        None
+    };
+    start_rendering(model, orders, known);
+}
+
+pub fn adjust_height_to_ratio(model: &mut Model) {
+    let (min, max) = active_area(&model.config);
+    let Some(height) = height_for_ratio(model.width, min, max) else {
+        log!(format!("keeping the height, the area {min} - {max} is empty"));
+        return;
+    };
+    model.height = height;
 }
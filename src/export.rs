@@ -0,0 +1,232 @@
+use png::{BitDepth, ColorType, Decoder, Encoder};
+use seed::prelude::{js_sys, web_sys, Closure, JsCast, JsValue};
+use seed::JsFuture;
+use seed::prelude::web_sys::{
+    Blob, BlobPropertyBag, HtmlAnchorElement, PermissionState, PermissionStatus, Url,
+};
+use std::future::Future;
+
+use super::{migration::migrate, validation, Config, FractalType};
+
+// tEXt keyword holding the serialized Config, see PNG spec for reserved keywords
+pub const CONFIG_KEYWORD: &str = "seed-fractals config";
+pub const SOFTWARE: &str = "seed-fractals";
+// delay before the object url of a download is revoked, browsers may fetch it after click() returns
+const REVOKE_DELAY_MS: i32 = 10_000;
+
+/// PNG text chunks describing an image of `config`, the config can be restored from them.
+pub fn png_text(config: &Config) -> Result<Vec<(&'static str, String)>, String> {
+    let json = serde_json::to_string(config)
+        .map_err(|err| format!("failed to serialize config: {err}"))?;
+    Ok(vec![
+        ("Software", SOFTWARE.to_string()),
+        ("Title", format!("{:?}", config.active_config)),
+        (CONFIG_KEYWORD, json),
+    ])
+}
+
+/// Encode an RGBA pixel buffer as PNG, adding each of `text` as a tEXt chunk.
+pub fn encode_png(
+    width: u32,
+    height: u32,
+    rgba: &[u8],
+    text: &[(&str, String)],
+) -> Result<Vec<u8>, String> {
+    let mut data = Vec::new();
+    let mut encoder = Encoder::new(&mut data, width, height);
+    encoder.set_color(ColorType::Rgba);
+    encoder.set_depth(BitDepth::Eight);
+    for (keyword, value) in text {
+        encoder
+            .add_text_chunk((*keyword).to_string(), value.clone())
+            .map_err(|err| format!("failed to add text chunk {keyword}: {err}"))?;
+    }
+    let mut writer = encoder
+        .write_header()
+        .map_err(|err| format!("failed to write png header: {err}"))?;
+    writer
+        .write_image_data(rgba)
+        .map_err(|err| format!("failed to write png data: {err}"))?;
+    writer
+        .finish()
+        .map_err(|err| format!("failed to finish png: {err}"))?;
+    Ok(data)
+}
+
+/// Restore the config embedded by `png_text` in an exported image, migrating older versions.
+/// The image replaces the whole config so the settings of every fractal type are validated.
+pub fn config_from_png(data: &[u8]) -> Result<Config, String> {
+    let reader = Decoder::new(data)
+        .read_info()
+        .map_err(|err| format!("invalid png: {err}"))?;
+    let json = reader
+        .info()
+        .uncompressed_latin1_text
+        .iter()
+        .find(|chunk| chunk.keyword == CONFIG_KEYWORD)
+        .map(|chunk| chunk.text.as_str())
+        .ok_or("The image holds no fractal settings")?;
+    let value = serde_json::from_str(json).map_err(|err| format!("invalid config: {err}"))?;
+    let config = migrate(value)?;
+    for fractal_type in [
+        FractalType::Mandelbrot,
+        FractalType::JuliaSet,
+        FractalType::BurningShip,
+        FractalType::Newton,
+    ] {
+        validation::check(&config, fractal_type)
+            .map_err(|err| format!("invalid {fractal_type:?} settings: {err}"))?;
+    }
+    Ok(config)
+}
+
+fn to_blob(data: &[u8], mime_type: &str) -> Result<Blob, JsValue> {
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(data));
+    let mut options = BlobPropertyBag::new();
+    options.type_(mime_type);
+    Blob::new_with_u8_array_sequence_and_options(&parts, &options)
+}
+
+fn js_error(err: &JsValue) -> String {
+    err.as_string().unwrap_or_else(|| format!("{err:?}"))
+}
+
+/// Offer `data` as a file download by clicking a temporary object URL link.
+pub fn download(data: &[u8], mime_type: &str, file_name: &str) -> Result<(), JsValue> {
+    let blob = to_blob(data, mime_type)?;
+    let url = Url::create_object_url_with_blob(&blob)?;
+
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("Window not found"))?;
+    let document = window
+        .document()
+        .ok_or_else(|| JsValue::from_str("document not found"))?;
+    let anchor = document
+        .create_element("a")?
+        .dyn_into::<HtmlAnchorElement>()?;
+    anchor.set_href(url.as_str());
+    anchor.set_download(file_name);
+    anchor.click();
+
+    let revoke = Closure::once_into_js(move || {
+        let _ = Url::revoke_object_url(url.as_str());
+    });
+    window.set_timeout_with_callback_and_timeout_and_arguments_0(
+        revoke.unchecked_ref(),
+        REVOKE_DELAY_MS,
+    )?;
+    Ok(())
+}
+
+/// True if writing to the clipboard was denied, e.g. to disable copying up front. The permission
+/// query starts before returning, browsers not knowing the clipboard-write permission allow
+/// the attempt.
+#[allow(clippy::future_not_send)]
+pub fn clipboard_write_denied() -> impl Future<Output = bool> {
+    let query = web_sys::window()
+        .and_then(|window| window.navigator().permissions().ok())
+        .and_then(|permissions| {
+            let descriptor = js_sys::Object::new();
+            js_sys::Reflect::set(&descriptor, &"name".into(), &"clipboard-write".into()).ok()?;
+            permissions.query(&descriptor).ok()
+        })
+        .map(JsFuture::from);
+    async move {
+        match query {
+            Some(query) => query.await.is_ok_and(|status| {
+                PermissionStatus::from(status).state() == PermissionState::Denied
+            }),
+            None => false,
+        }
+    }
+}
+
+/// Write a PNG image to the system clipboard using the async Clipboard API. Browsers only allow
+/// this during a user gesture, so the write starts before returning and the future only waits
+/// for its result.
+pub fn copy_to_clipboard(data: &[u8]) -> Result<impl Future<Output = Result<(), String>>, String> {
+    let window = web_sys::window().ok_or("Window not found")?;
+    let clipboard = window
+        .navigator()
+        .clipboard()
+        .ok_or("Clipboard is not available, a secure context (https) is required")?;
+    // web-sys has no constructor for ClipboardItem yet
+    let item_class = js_sys::Reflect::get(&window, &"ClipboardItem".into())
+        .ok()
+        .and_then(|class| class.dyn_into::<js_sys::Function>().ok())
+        .ok_or("Copying images is not supported by this browser")?;
+
+    let blob = to_blob(data, "image/png").map_err(|err| js_error(&err))?;
+    let record = js_sys::Object::new();
+    js_sys::Reflect::set(&record, &"image/png".into(), &blob).map_err(|err| js_error(&err))?;
+    let item = js_sys::Reflect::construct(&item_class, &js_sys::Array::of1(&record))
+        .map_err(|err| js_error(&err))?;
+
+    let write = JsFuture::from(clipboard.write(&js_sys::Array::of1(&item)));
+    Ok(async move {
+        write.await.map(|_| ()).map_err(|err| {
+            let name = js_sys::Reflect::get(&err, &"name".into()).ok();
+            if name.and_then(|name| name.as_string()).as_deref() == Some("NotAllowedError") {
+                "Permission to write to the clipboard was denied".to_string()
+            } else {
+                js_error(&err)
+            }
+        })
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{config_from_png, encode_png, png_text, CONFIG_KEYWORD};
+    use crate::{complex::Complex, Config, FractalType};
+
+    #[test]
+    fn test_encode_png() {
+        let rgba = [0xFF, 0x00, 0x00, 0xFF, 0x00, 0x00, 0xFF, 0xFF];
+        let data = encode_png(2, 1, &rgba, &[(CONFIG_KEYWORD, "{\"a\":1}".to_string())])
+            .expect("encoding failed");
+
+        let decoder = png::Decoder::new(data.as_slice());
+        let mut reader = decoder.read_info().expect("decoding failed");
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut pixels).expect("no frame found");
+        assert_eq!(pixels, rgba);
+
+        let text = &reader.info().uncompressed_latin1_text;
+        assert_eq!(text.len(), 1);
+        assert_eq!(text[0].keyword, CONFIG_KEYWORD);
+        assert_eq!(text[0].text, "{\"a\":1}");
+    }
+
+    #[test]
+    fn test_config_from_png() {
+        let mut config = Config {
+            active_config: FractalType::JuliaSet,
+            ..Config::default()
+        };
+        config.julia_set_cfg.max_iterations = 123;
+        let text = png_text(&config).expect("serializable config");
+        let data = encode_png(1, 1, &[0, 0, 0, 0xFF], &text).expect("encoding failed");
+        let restored = config_from_png(&data).expect("config expected");
+        assert_eq!(restored.active_config, FractalType::JuliaSet);
+        assert_eq!(restored.julia_set_cfg, config.julia_set_cfg);
+
+        let data = encode_png(1, 1, &[0, 0, 0, 0xFF], &[]).expect("encoding failed");
+        assert!(config_from_png(&data).is_err());
+        assert!(config_from_png(b"not a png").is_err());
+    }
+
+    #[test]
+    fn test_invalid_config_from_png() {
+        let mut config = Config::default();
+        config.julia_set_cfg.max_iterations = 0;
+        let text = png_text(&config).expect("serializable config");
+        let data = encode_png(1, 1, &[0, 0, 0, 0xFF], &text).expect("encoding failed");
+        assert!(config_from_png(&data).is_err());
+
+        let mut config = Config::default();
+        config.mandelbrot_cfg.c_max = config.mandelbrot_cfg.c_min + Complex::new(1e-9, 1.0);
+        let text = png_text(&config).expect("serializable config");
+        let data = encode_png(1, 1, &[0, 0, 0, 0xFF], &text).expect("encoding failed");
+        assert!(config_from_png(&data).is_err());
+    }
+}
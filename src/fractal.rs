@@ -1,26 +1,496 @@
-use super::stats::Stats;
+use serde::{Deserialize, Serialize};
 
-const MAX_POINTS: usize = 5000;
+use super::{stats::Stats, Renderer};
 
-pub struct Points {
+pub const NO_ROOT: u8 = u8::MAX;
+
+/// Work done while calculating points, reported to `Stats`.
+#[derive(Default)]
+pub struct Counts {
+    pub iterations: usize,
+    // points known to be inside without iterating to the end
+    pub short_circuited: usize,
+}
+
+/// Rectangular part of an image, the unit of work of the renderer. With a `block` size > 1 only
+/// the top left corner of every `block` x `block` square of the tile is calculated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tile {
+    pub id: usize,
     pub x_start: u32,
     pub y_start: u32,
-    pub num_points: usize,
-    pub values: [u32; MAX_POINTS],
+    pub width: u32,
+    pub height: u32,
+    pub block: u32,
+}
+
+impl Tile {
+    /// Split a `width` x `height` image into tiles of up to `size` x `size` pixels, row by row.
+    /// The id of a tile is its index.
+    pub fn split(width: u32, height: u32, size: u32) -> Vec<Self> {
+        let mut tiles = Vec::new();
+        for y_start in (0..height).step_by(size as usize) {
+            for x_start in (0..width).step_by(size as usize) {
+                tiles.push(Self {
+                    id: tiles.len(),
+                    x_start,
+                    y_start,
+                    width: u32::min(size, width - x_start),
+                    height: u32::min(size, height - y_start),
+                    block: 1,
+                });
+            }
+        }
+        tiles
+    }
+
+    pub const fn with_block(&self, block: u32) -> Self {
+        Self { block, ..*self }
+    }
+
+    /// Number of pixels covered.
+    pub const fn len(&self) -> usize {
+        self.width as usize * self.height as usize
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of points calculated, one per block.
+    pub const fn num_points(&self) -> usize {
+        self.width.div_ceil(self.block) as usize * self.height.div_ceil(self.block) as usize
+    }
+
+    /// Pixel position of point `idx`, points are numbered row by row.
+    #[allow(clippy::cast_possible_truncation)]
+    pub const fn position(&self, idx: usize) -> (u32, u32) {
+        let columns = self.width.div_ceil(self.block) as usize;
+        (
+            self.x_start + (idx % columns) as u32 * self.block,
+            self.y_start + (idx / columns) as u32 * self.block,
+        )
+    }
+
+    pub fn center(&self) -> (f64, f64) {
+        (
+            f64::from(self.x_start) + f64::from(self.width) / 2.0,
+            f64::from(self.y_start) + f64::from(self.height) / 2.0,
+        )
+    }
+}
+
+/// Results of a tile, one point per block of the tile.
+pub struct Points {
+    pub tile: Tile,
+    pub values: Vec<u32>,
+    // fractional iteration count, only used by escape time fractals
+    pub smooth: Vec<f32>,
+    // index of the root a point converged to, only used by root finding fractals
+    pub roots: Vec<u8>,
+}
+
+impl Points {
+    pub fn new(tile: Tile) -> Self {
+        let num_points = tile.num_points();
+        Self {
+            tile,
+            values: vec![0; num_points],
+            smooth: vec![0.0; num_points],
+            roots: vec![NO_ROOT; num_points],
+        }
+    }
+}
+
+/// Keeps the results of a render so it can be recolored without recalculation.
+pub struct IterationBuffer {
+    width: u32,
+    height: u32,
+    filled: usize,
+    pub values: Vec<u32>,
+    pub smooth: Vec<f32>,
+    pub roots: Vec<u8>,
+    // block size of the pass a point was stored by, 0 if it was not stored yet
+    blocks: Vec<u8>,
+}
+
+impl IterationBuffer {
+    pub fn new(width: u32, height: u32) -> Self {
+        let size = width as usize * height as usize;
+        Self {
+            width,
+            height,
+            filled: 0,
+            values: vec![0; size],
+            smooth: vec![0.0; size],
+            roots: vec![NO_ROOT; size],
+            blocks: vec![0; size],
+        }
+    }
+
+    /// Store `points`, a point of a coarse pass fills its block.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn store(&mut self, points: &Points) {
+        let tile = &points.tile;
+        let x_end = u32::min(tile.x_start + tile.width, self.width);
+        let y_end = u32::min(tile.y_start + tile.height, self.height);
+        let width = self.width as usize;
+        for idx in 0..tile.num_points() {
+            let (x, y) = tile.position(idx);
+            for row in y..u32::min(y + tile.block, y_end) {
+                let start = row as usize * width + x as usize;
+                let end = row as usize * width + u32::min(x + tile.block, x_end) as usize;
+                for pos in start..end {
+                    if tile.block == 1 && self.blocks[pos] != 1 {
+                        self.filled += 1;
+                    }
+                    self.values[pos] = points.values[idx];
+                    self.smooth[pos] = points.smooth[idx];
+                    self.roots[pos] = points.roots[idx];
+                    self.blocks[pos] = tile.block as u8;
+                }
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.filled = 0;
+        self.blocks.iter_mut().for_each(|block| *block = 0);
+    }
+
+    pub const fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub const fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// True if point `idx` was stored by any pass.
+    pub fn is_stored(&self, idx: usize) -> bool {
+        self.blocks[idx] != 0
+    }
+
+    /// True if point `idx` was calculated at full resolution.
+    pub fn is_final(&self, idx: usize) -> bool {
+        self.blocks[idx] == 1
+    }
+
+    /// Store the points of `known` as calculated at full resolution.
+    pub fn store_known(&mut self, known: &KnownPoints) {
+        let columns = known.buffer.width as usize;
+        for (idx, _) in known.known.iter().enumerate().filter(|(_, known)| **known) {
+            let x = known.x_start as usize + idx % columns;
+            let y = known.y_start as usize + idx / columns;
+            if x >= self.width as usize || y >= self.height as usize {
+                continue;
+            }
+            let pos = y * self.width as usize + x;
+            if self.blocks[pos] != 1 {
+                self.filled += 1;
+            }
+            self.values[pos] = known.buffer.values[idx];
+            self.smooth[pos] = known.buffer.smooth[idx];
+            self.roots[pos] = known.buffer.roots[idx];
+            self.blocks[pos] = 1;
+        }
+    }
+
+    /// Iterations, smooth iterations and root of a point calculated at its position, by the full
+    /// resolution or a coarse pass. Tiles start at multiples of the block sizes, so a coarse sample
+    /// sits at the top left corner of its block, the other points of the block only repeat it.
+    pub fn known(&self, x: u32, y: u32) -> Option<(u32, f32, u8)> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let idx = y as usize * self.width as usize + x as usize;
+        let block = u32::from(self.blocks[idx]);
+        if block != 0 && x.is_multiple_of(block) && y.is_multiple_of(block) {
+            Some((self.values[idx], self.smooth[idx], self.roots[idx]))
+        } else {
+            None
+        }
+    }
+
+    /// True if a finer pass of `tile` was stored already, a coarse result arriving late is dropped.
+    pub fn has_finer(&self, tile: &Tile) -> bool {
+        let block = self.blocks[tile.y_start as usize * self.width as usize + tile.x_start as usize];
+        block != 0 && u32::from(block) < tile.block
+    }
+
+    /// Number of points calculated at full resolution.
+    pub const fn filled(&self) -> usize {
+        self.filled
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.filled == 0
+    }
 }
 
-impl Default for Points {
-    fn default() -> Self {
+/// Points with known results, either from a previous render moved to their position in a
+/// panned view or from a coarser pass. These points are copied instead of being calculated again.
+/// Only points within the covered area are kept, a worker only needs the points of its tile.
+pub struct KnownPoints {
+    // top left corner of the covered area, the buffer has its size
+    x_start: u32,
+    y_start: u32,
+    buffer: IterationBuffer,
+    known: Vec<bool>,
+}
+
+impl KnownPoints {
+    /// Cover a whole `width` x `height` image.
+    pub fn new(width: u32, height: u32) -> Self {
+        let buffer = IterationBuffer::new(width, height);
+        let known = vec![false; buffer.values.len()];
         Self {
             x_start: 0,
             y_start: 0,
-            num_points: 0,
-            values: [0; MAX_POINTS],
+            buffer,
+            known,
+        }
+    }
+
+    /// Cover the pixels of `tile` only.
+    pub fn for_tile(tile: &Tile) -> Self {
+        Self {
+            x_start: tile.x_start,
+            y_start: tile.y_start,
+            ..Self::new(tile.width, tile.height)
+        }
+    }
+
+    /// The points of `tile` stored in `buffer` by a coarser pass or copied from a panned view.
+    pub fn from_buffer(buffer: &IterationBuffer, tile: &Tile) -> Self {
+        let mut known = Self::for_tile(tile);
+        for idx in 0..tile.num_points() {
+            let (x, y) = tile.position(idx);
+            if let Some(point) = buffer.known(x, y) {
+                known.insert(x, y, point);
+            }
+        }
+        known
+    }
+
+    // index of pixel x, y in the buffer, None outside of the covered area
+    fn index(&self, x: u32, y: u32) -> Option<usize> {
+        let x = x.checked_sub(self.x_start)?;
+        let y = y.checked_sub(self.y_start)?;
+        if x < self.buffer.width && y < self.buffer.height {
+            Some(y as usize * self.buffer.width as usize + x as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn insert(&mut self, x: u32, y: u32, point: (u32, f32, u8)) {
+        if let Some(idx) = self.index(x, y) {
+            self.buffer.values[idx] = point.0;
+            self.buffer.smooth[idx] = point.1;
+            self.buffer.roots[idx] = point.2;
+            self.known[idx] = true;
+        }
+    }
+
+    /// Shift the points in `buffer` by `dx`, `dy` pixels, points moved out of the image are dropped.
+    #[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+    pub fn shifted(buffer: &IterationBuffer, dx: i32, dy: i32) -> Self {
+        let width = buffer.width as i32;
+        let height = buffer.height as i32;
+        let mut shifted = Self::new(buffer.width, buffer.height);
+
+        for src_y in i32::max(0, -dy)..i32::min(height, height - dy) {
+            for src_x in i32::max(0, -dx)..i32::min(width, width - dx) {
+                let src = (src_y * width + src_x) as usize;
+                // only points that were calculated before can be reused
+                if buffer.is_final(src) {
+                    shifted.insert(
+                        (src_x + dx) as u32,
+                        (src_y + dy) as u32,
+                        (buffer.values[src], buffer.smooth[src], buffer.roots[src]),
+                    );
+                }
+            }
+        }
+        shifted
+    }
+
+    /// The points of `tile` if all of them are known.
+    pub fn tile_points(&self, tile: &Tile) -> Option<Points> {
+        let mut points = Points::new(*tile);
+        for idx in 0..tile.num_points() {
+            let (x, y) = tile.position(idx);
+            let (value, smooth, root) = self.get(x, y)?;
+            points.values[idx] = value;
+            points.smooth[idx] = smooth;
+            points.roots[idx] = root;
+        }
+        Some(points)
+    }
+
+    /// Iterations, smooth iterations and root of a point, if it is known.
+    pub fn get(&self, x: u32, y: u32) -> Option<(u32, f32, u8)> {
+        let idx = self.index(x, y)?;
+        if self.known[idx] {
+            Some((self.buffer.values[idx], self.buffer.smooth[idx], self.buffer.roots[idx]))
+        } else {
+            None
         }
     }
 }
 
 pub trait Fractal {
-    fn calculate(&mut self, stats: Option<&mut Stats>) -> &Points;
-    fn is_done(&self) -> bool;
+    /// Calculate the points of `tile`, one per block.
+    fn calculate(&mut self, tile: &Tile, stats: Option<&mut Stats>) -> Points;
+    fn set_known_points(&mut self, known: KnownPoints);
+    fn set_renderer(&mut self, renderer: Renderer);
+}
+
+#[cfg(test)]
+mod test {
+    use super::{IterationBuffer, KnownPoints, Points, Tile, NO_ROOT};
+
+    #[test]
+    fn test_tiles() {
+        let tiles = Tile::split(5, 3, 2);
+        assert_eq!(tiles.len(), 6);
+        assert_eq!(tiles.iter().map(Tile::len).sum::<usize>(), 15);
+        assert_eq!(
+            tiles[5],
+            Tile {
+                id: 5,
+                x_start: 4,
+                y_start: 2,
+                width: 1,
+                height: 1,
+                block: 1,
+            }
+        );
+
+        let tile = Tile::split(5, 3, 8)[0].with_block(2);
+        assert_eq!(tile.num_points(), 6);
+        assert_eq!(tile.position(2), (4, 0));
+        assert_eq!(tile.position(4), (2, 2));
+    }
+
+    #[test]
+    fn test_store() {
+        let mut buffer = IterationBuffer::new(5, 3);
+        assert!(buffer.is_empty());
+
+        let tiles = Tile::split(5, 3, 2);
+        let mut points = Points::new(tiles[1]);
+        points.values.copy_from_slice(&[1, 2, 3, 4]);
+        buffer.store(&points);
+        assert_eq!(buffer.values[0..5], [0, 0, 1, 2, 0]);
+        assert_eq!(buffer.values[5..10], [0, 0, 3, 4, 0]);
+        assert_eq!(buffer.filled(), 4);
+        assert!(buffer.is_final(2));
+        assert!(!buffer.is_stored(0));
+
+        // storing a point again does not count twice
+        buffer.store(&points);
+        assert_eq!(buffer.filled(), 4);
+
+        buffer.clear();
+        assert!(buffer.is_empty());
+        assert!(!buffer.is_stored(2));
+    }
+
+    #[test]
+    fn test_store_blocks() {
+        let mut buffer = IterationBuffer::new(5, 3);
+        let tile = Tile::split(5, 3, 8)[0].with_block(2);
+        let mut points = Points::new(tile);
+        points.values.copy_from_slice(&[1, 2, 3, 4, 5, 6]);
+        buffer.store(&points);
+
+        assert_eq!(buffer.values[0..5], [1, 1, 2, 2, 3]);
+        assert_eq!(buffer.values[5..10], [1, 1, 2, 2, 3]);
+        assert_eq!(buffer.values[10..15], [4, 4, 5, 5, 6]);
+        // coarse points are not final
+        assert!(buffer.is_empty());
+        assert!(buffer.is_stored(14));
+        assert!(!buffer.is_final(14));
+        assert!(!buffer.has_finer(&tile));
+        assert!(buffer.has_finer(&tile.with_block(4)));
+        assert!(!buffer.has_finer(&tile.with_block(1)));
+        // only the samples are known
+        assert_eq!(buffer.known(2, 2), Some((5, 0.0, NO_ROOT)));
+        assert_eq!(buffer.known(3, 2), None);
+        assert_eq!(buffer.known(5, 0), None);
+
+        // a finer pass gets the samples as known points
+        let known = KnownPoints::from_buffer(&buffer, &tile.with_block(1));
+        assert_eq!(known.get(2, 2), Some((5, 0.0, NO_ROOT)));
+        assert_eq!(known.get(3, 2), None);
+    }
+
+    #[test]
+    fn test_known_points() {
+        let mut buffer = IterationBuffer::new(3, 3);
+        let tile = Tile::split(3, 3, 3)[0];
+        let mut points = Points::new(tile);
+        points.values.copy_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        buffer.store(&points);
+        // the last row is only a preview
+        let mut preview = Points::new(
+            Tile {
+                y_start: 2,
+                height: 1,
+                ..tile
+            }
+            .with_block(2),
+        );
+        preview.values.copy_from_slice(&[0, 0]);
+        buffer.store(&preview);
+
+        // move right and up, the last row was never calculated
+        let known = KnownPoints::shifted(&buffer, 1, -1);
+        assert_eq!(known.get(0, 0), None);
+        assert_eq!(known.get(1, 0).map(|point| point.0), Some(4));
+        assert_eq!(known.get(2, 0).map(|point| point.0), Some(5));
+        assert_eq!(known.get(1, 1), None);
+        assert_eq!(known.get(1, 2), None);
+        assert_eq!(known.get(3, 0), None);
+
+        let tiles = Tile::split(3, 3, 2);
+        assert!(known.tile_points(&tiles[0]).is_none());
+        let points = known.tile_points(&Tile {
+            x_start: 1,
+            height: 1,
+            ..tiles[0]
+        });
+        assert_eq!(points.map(|points| points.values), Some(vec![4, 5]));
+
+        let mut panned = IterationBuffer::new(3, 3);
+        panned.store_known(&known);
+        assert_eq!(panned.filled(), 2);
+        assert_eq!(panned.known(1, 0).map(|point| point.0), Some(4));
+        assert!(!panned.is_stored(0));
+    }
+
+    #[test]
+    fn test_tile_known_points() {
+        let tile = Tile::split(5, 3, 2)[4];
+        let mut known = KnownPoints::for_tile(&tile);
+        known.insert(2, 2, (7, 0.5, NO_ROOT));
+        known.insert(3, 2, (8, 0.0, NO_ROOT));
+        // outside of the tile
+        known.insert(1, 2, (9, 0.0, NO_ROOT));
+        known.insert(2, 1, (9, 0.0, NO_ROOT));
+        assert_eq!(known.get(2, 2), Some((7, 0.5, NO_ROOT)));
+        assert_eq!(known.get(1, 2), None);
+        assert_eq!(known.get(2, 1), None);
+        assert_eq!(
+            known.tile_points(&tile).map(|points| points.values),
+            Some(vec![7, 8])
+        );
+
+        let mut buffer = IterationBuffer::new(5, 3);
+        buffer.store_known(&known);
+        assert_eq!(buffer.filled(), 2);
+        assert_eq!(buffer.values[10..15], [0, 0, 7, 8, 0]);
+    }
 }
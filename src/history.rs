@@ -0,0 +1,259 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use super::{BurningShipCfg, Config, FractalType, JuliaSetCfg, MandelbrotCfg, NewtonCfg};
+
+// number of states kept per fractal type
+pub const HISTORY_SIZE: usize = 32;
+
+/// Bounded undo / redo stack of states.
+#[derive(Serialize, Deserialize)]
+pub struct History<T> {
+    back: VecDeque<T>,
+    forward: Vec<T>,
+    // state saved when an edit was started, only pushed once the edit is saved
+    #[serde(skip)]
+    pending: Option<T>,
+}
+
+impl<T> Default for History<T> {
+    fn default() -> Self {
+        Self {
+            back: VecDeque::new(),
+            forward: Vec::new(),
+            pending: None,
+        }
+    }
+}
+
+impl<T: Clone + PartialEq> History<T> {
+    /// Remember `state` as the state before a change, this discards the redo stack.
+    pub fn push(&mut self, state: T) {
+        self.back.push_back(state);
+        if self.back.len() > HISTORY_SIZE {
+            self.back.pop_front();
+        }
+        self.forward.clear();
+    }
+
+    pub fn back(&mut self, current: T) -> Option<T> {
+        let previous = self.back.pop_back()?;
+        self.forward.push(current);
+        Some(previous)
+    }
+
+    pub fn forward(&mut self, current: T) -> Option<T> {
+        let next = self.forward.pop()?;
+        self.back.push_back(current);
+        Some(next)
+    }
+
+    pub fn can_back(&self) -> bool {
+        !self.back.is_empty()
+    }
+
+    pub const fn can_forward(&self) -> bool {
+        !self.forward.is_empty()
+    }
+
+    pub fn begin(&mut self, state: T) {
+        self.pending = Some(state);
+    }
+
+    /// Push the state saved in `begin` unless nothing was changed.
+    pub fn commit(&mut self, current: &T) {
+        if let Some(state) = self.pending.take() {
+            if state != *current {
+                self.push(state);
+            }
+        }
+    }
+
+    pub const fn rollback(&mut self) -> Option<T> {
+        self.pending.take()
+    }
+}
+
+/// Navigation history of every fractal type, persisted next to `Config`.
+#[derive(Default, Serialize, Deserialize)]
+pub struct Histories {
+    julia_set: History<JuliaSetCfg>,
+    mandelbrot: History<MandelbrotCfg>,
+    burning_ship: History<BurningShipCfg>,
+    newton: History<NewtonCfg>,
+}
+
+impl Histories {
+    /// Remember the active configuration before it is changed.
+    pub fn record(&mut self, config: &Config) {
+        match config.active_config {
+            FractalType::JuliaSet => self.julia_set.push(config.julia_set_cfg.clone()),
+            FractalType::Mandelbrot => self.mandelbrot.push(config.mandelbrot_cfg.clone()),
+            FractalType::BurningShip => self.burning_ship.push(config.burning_ship_cfg.clone()),
+            FractalType::Newton => self.newton.push(config.newton_cfg.clone()),
+        }
+    }
+
+    pub fn begin(&mut self, config: &Config) {
+        match config.active_config {
+            FractalType::JuliaSet => self.julia_set.begin(config.julia_set_cfg.clone()),
+            FractalType::Mandelbrot => self.mandelbrot.begin(config.mandelbrot_cfg.clone()),
+            FractalType::BurningShip => self.burning_ship.begin(config.burning_ship_cfg.clone()),
+            FractalType::Newton => self.newton.begin(config.newton_cfg.clone()),
+        }
+    }
+
+    pub fn commit(&mut self, config: &Config) {
+        match config.active_config {
+            FractalType::JuliaSet => self.julia_set.commit(&config.julia_set_cfg),
+            FractalType::Mandelbrot => self.mandelbrot.commit(&config.mandelbrot_cfg),
+            FractalType::BurningShip => self.burning_ship.commit(&config.burning_ship_cfg),
+            FractalType::Newton => self.newton.commit(&config.newton_cfg),
+        }
+    }
+
+    /// Restore the active configuration saved in `begin`.
+    pub fn rollback(&mut self, config: &mut Config) {
+        match config.active_config {
+            FractalType::JuliaSet => {
+                if let Some(state) = self.julia_set.rollback() {
+                    config.julia_set_cfg = state;
+                }
+            }
+            FractalType::Mandelbrot => {
+                if let Some(state) = self.mandelbrot.rollback() {
+                    config.mandelbrot_cfg = state;
+                }
+            }
+            FractalType::BurningShip => {
+                if let Some(state) = self.burning_ship.rollback() {
+                    config.burning_ship_cfg = state;
+                }
+            }
+            FractalType::Newton => {
+                if let Some(state) = self.newton.rollback() {
+                    config.newton_cfg = state;
+                }
+            }
+        }
+    }
+
+    /// Step back in the history of the active fractal type, returns false if there is nothing to restore.
+    pub fn back(&mut self, config: &mut Config) -> bool {
+        match config.active_config {
+            FractalType::JuliaSet => self
+                .julia_set
+                .back(config.julia_set_cfg.clone())
+                .map(|state| config.julia_set_cfg = state),
+            FractalType::Mandelbrot => self
+                .mandelbrot
+                .back(config.mandelbrot_cfg.clone())
+                .map(|state| config.mandelbrot_cfg = state),
+            FractalType::BurningShip => self
+                .burning_ship
+                .back(config.burning_ship_cfg.clone())
+                .map(|state| config.burning_ship_cfg = state),
+            FractalType::Newton => self
+                .newton
+                .back(config.newton_cfg.clone())
+                .map(|state| config.newton_cfg = state),
+        }
+        .is_some()
+    }
+
+    /// Step forward in the history of the active fractal type, returns false if there is nothing to restore.
+    pub fn forward(&mut self, config: &mut Config) -> bool {
+        match config.active_config {
+            FractalType::JuliaSet => self
+                .julia_set
+                .forward(config.julia_set_cfg.clone())
+                .map(|state| config.julia_set_cfg = state),
+            FractalType::Mandelbrot => self
+                .mandelbrot
+                .forward(config.mandelbrot_cfg.clone())
+                .map(|state| config.mandelbrot_cfg = state),
+            FractalType::BurningShip => self
+                .burning_ship
+                .forward(config.burning_ship_cfg.clone())
+                .map(|state| config.burning_ship_cfg = state),
+            FractalType::Newton => self
+                .newton
+                .forward(config.newton_cfg.clone())
+                .map(|state| config.newton_cfg = state),
+        }
+        .is_some()
+    }
+
+    pub fn can_back(&self, fractal_type: FractalType) -> bool {
+        match fractal_type {
+            FractalType::JuliaSet => self.julia_set.can_back(),
+            FractalType::Mandelbrot => self.mandelbrot.can_back(),
+            FractalType::BurningShip => self.burning_ship.can_back(),
+            FractalType::Newton => self.newton.can_back(),
+        }
+    }
+
+    pub const fn can_forward(&self, fractal_type: FractalType) -> bool {
+        match fractal_type {
+            FractalType::JuliaSet => self.julia_set.can_forward(),
+            FractalType::Mandelbrot => self.mandelbrot.can_forward(),
+            FractalType::BurningShip => self.burning_ship.can_forward(),
+            FractalType::Newton => self.newton.can_forward(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{History, HISTORY_SIZE};
+
+    #[test]
+    fn test_back_forward() {
+        let mut history = History::default();
+        history.push(1);
+        history.push(2);
+        assert_eq!(history.back(3), Some(2));
+        assert_eq!(history.back(2), Some(1));
+        assert_eq!(history.back(1), None);
+        assert_eq!(history.forward(1), Some(2));
+        assert!(history.can_forward());
+
+        // a new state discards the redo stack
+        history.push(2);
+        assert!(!history.can_forward());
+        assert_eq!(history.back(4), Some(2));
+    }
+
+    #[test]
+    fn test_bounded() {
+        let mut history = History::default();
+        for state in 0..HISTORY_SIZE + 5 {
+            history.push(state);
+        }
+        let mut count = 0;
+        let mut current = HISTORY_SIZE + 5;
+        while let Some(state) = history.back(current) {
+            current = state;
+            count += 1;
+        }
+        assert_eq!(count, HISTORY_SIZE);
+        assert_eq!(current, 5);
+    }
+
+    #[test]
+    fn test_commit() {
+        let mut history = History::default();
+        history.begin(1);
+        history.commit(&1);
+        assert!(!history.can_back());
+
+        history.begin(1);
+        history.commit(&2);
+        assert_eq!(history.back(2), Some(1));
+
+        history.begin(3);
+        assert_eq!(history.rollback(), Some(3));
+        assert_eq!(history.rollback(), None);
+    }
+}
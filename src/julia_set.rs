@@ -1,146 +1,239 @@
-use seed::log;
-// use wasm_bindgen::prelude::web_sys;
-use super::{util::find_escape_radius, MAX_DURATION};
-use seed::prelude::web_sys;
+use super::util::{
+    find_escape_radius, integer_exponent, precise_digits, smooth_iterations, PERIOD_TOLERANCE,
+    SMOOTH_BAILOUT,
+};
 
 use super::{
+    bigfloat::BigComplex,
+    clock::Clock,
     complex::Complex,
-    fractal::{Fractal, Points},
-    Model,
-    stats::Stats
+    fractal::{Counts, Fractal, KnownPoints, Points, Tile},
+    stats::Stats,
+    subdivision::Subdivision,
+    Renderer,
 };
 
+/// Area, constant and iteration settings of a Julia set image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JuliaSetParams {
+    // the corners are relative to the origin
+    pub origin: BigComplex,
+    pub x_min: Complex,
+    pub x_max: Complex,
+    pub c: Complex,
+    pub iterations: u32,
+    pub exponent: f64,
+    pub periodicity_check: bool,
+}
+
 pub struct JuliaSet {
     scale_real: f64,
     scale_imag: f64,
     offset: Complex,
+    origin: BigComplex,
+    origin_f64: Complex,
+    // decimal places of the arbitrary precision iteration of deep zooms
+    precision: Option<usize>,
     c: Complex,
+    big_c: BigComplex,
     max: f64,
-    x_curr: u32,
     width: u32,
     height: u32,
-    y_curr: u32,
     iterations: u32,
-    res: Points,
-    done: bool,
+    exponent: f64,
+    int_exponent: Option<u32>,
+    periodicity_check: bool,
+    // squared distance at which an orbit counts as periodic
+    period_epsilon: f64,
+    known: Option<KnownPoints>,
+    // Mariani-Silver subdivision for the final pass
+    subdivision: bool,
+    clock: Box<dyn Clock>,
 }
 
 impl JuliaSet {
-    pub fn new(model: &Model) -> Self {
-        log!(format!(
-            "creating fractal with: x_max: {}, x_min: {}, c: {}",
-            model.config.julia_set_cfg.x_max,
-            model.config.julia_set_cfg.x_min,
-            model.config.julia_set_cfg.c
-        ));
-
-        let scale_real = (model.config.julia_set_cfg.x_max.real()
-            - model.config.julia_set_cfg.x_min.real())
-            / f64::from(model.width);
-        let scale_imag = (model.config.julia_set_cfg.x_max.imag()
-            - model.config.julia_set_cfg.x_min.imag())
-            / f64::from(model.height);
-        let max = find_escape_radius(model.config.julia_set_cfg.c.norm());
+    pub fn new(params: &JuliaSetParams, width: u32, height: u32, clock: Box<dyn Clock>) -> Self {
+        let scale_real = (params.x_max.real() - params.x_min.real()) / f64::from(width);
+        let scale_imag = (params.x_max.imag() - params.x_min.imag()) / f64::from(height);
+        let max = f64::max(
+            find_escape_radius(params.c.norm(), params.exponent),
+            SMOOTH_BAILOUT,
+        );
+        let origin_f64 = params.origin.to_complex();
+        let center = Complex::new(
+            origin_f64.real() + f64::midpoint(params.x_min.real(), params.x_max.real()),
+            origin_f64.imag() + f64::midpoint(params.x_min.imag(), params.x_max.imag()),
+        );
+        // non-integer exponents need powf, those stay with f64
+        let int_exponent = integer_exponent(params.exponent);
+        let precision = int_exponent.and_then(|_| {
+            precise_digits(f64::min(scale_real, scale_imag), center)
+        });
 
         Self {
             scale_real,
             scale_imag,
-            offset: model.config.julia_set_cfg.x_min,
-            c: model.config.julia_set_cfg.c,
+            offset: params.x_min,
+            origin: params.origin.clone(),
+            origin_f64,
+            precision,
+            c: params.c,
+            big_c: BigComplex::from(params.c),
             max: max * max,
-            x_curr: 0,
-            width: model.width,
-            y_curr: 0,
-            height: model.height,
-            iterations: model.config.julia_set_cfg.max_iterations,
-            res: Points::default(),
-            done: false,
+            width,
+            height,
+            iterations: params.iterations,
+            exponent: params.exponent,
+            int_exponent,
+            periodicity_check: params.periodicity_check,
+            period_epsilon: (f64::min(scale_real, scale_imag) * PERIOD_TOLERANCE).powi(2),
+            known: None,
+            subdivision: false,
+            clock,
         }
     }
 
-    fn iterate(&self, x: &Complex) -> u32 {
+    fn iterate(&self, x: &Complex, counts: &mut Counts) -> (u32, f32) {
+        #[allow(clippy::cast_precision_loss)]
+        let inside = (self.iterations + 1, (self.iterations + 1) as f32);
         let mut curr = *x;
-        // log!(format!("iterate: start: {}", curr));
-        let mut last: Option<u32> = None;
+        // Brent's cycle detection, the orbit is compared to a point saved after 1, 2, 4, .. steps
+        let mut saved = curr;
+        let mut cycle_len = 0u32;
+        let mut cycle_max = 1u32;
         for idx in 1..=self.iterations {
-            curr = curr * curr + self.c;
+            curr = match self.int_exponent {
+                Some(2) => curr * curr,
+                Some(exp) => curr.powi(exp),
+                None => curr.powf(self.exponent),
+            } + self.c;
             if curr.square_length() >= self.max {
-                last = Some(idx);
-                break;
+                counts.iterations += idx as usize;
+                return (idx, smooth_iterations(idx, curr.square_length(), self.exponent));
+            }
+            if self.periodicity_check {
+                if (curr - saved).square_length() < self.period_epsilon {
+                    counts.iterations += idx as usize;
+                    counts.short_circuited += 1;
+                    return inside;
+                }
+                cycle_len += 1;
+                if cycle_len == cycle_max {
+                    saved = curr;
+                    cycle_len = 0;
+                    cycle_max = cycle_max.saturating_mul(2);
+                }
             }
         }
 
-        // log!(format!("iterate: end:  {} norm: {} last: {:?}", curr, curr.square_length(), last));
-        last.map_or(self.iterations + 1, |last| last)    
+        counts.iterations += self.iterations as usize;
+        inside
     }
-}
 
-impl Fractal for JuliaSet {
-    fn calculate(&mut self, stats: Option<&mut Stats>) -> &Points {
-        let performance = web_sys::window()
-            .expect("Window not found")
-            .performance()
-            .expect("performance should be available");
-
-        let start = performance.now();
-
-        self.res.x_start = self.x_curr;
-        self.res.y_start = self.y_curr;
-        self.res.num_points = 0;
-
-        let mut x = self.x_curr;
-        let mut y = self.y_curr;
-
-        let mut points_done: Option<usize> = None;
-        let mut last_check = 0usize;
-        let mut iterations = 0usize;
-
-        for count in 0..self.res.values.len() {
-            let calc = Complex::new(
-                f64::from(x).mul_add(self.scale_real, self.offset.real()),
-                f64::from(y).mul_add(self.scale_imag, self.offset.imag()),
-            );
-            let curr = self.iterate(&calc);
-            self.res.values[count] = curr;
-
-            if x < self.width - 1{
-                x += 1;
+    // the same iteration with arbitrary precision, the escape and period checks only need f64
+    fn iterate_precise(&self, x: &BigComplex, digits: usize, counts: &mut Counts) -> (u32, f32) {
+        #[allow(clippy::cast_precision_loss)]
+        let inside = (self.iterations + 1, (self.iterations + 1) as f32);
+        let exponent = self.int_exponent.unwrap_or(2);
+        let mut curr = x.clone();
+        let mut saved = curr.clone();
+        let mut cycle_len = 0u32;
+        let mut cycle_max = 1u32;
+        for idx in 1..=self.iterations {
+            curr = &if exponent == 2 {
+                curr.square(digits)
             } else {
-                x = 0;
-                y += 1;
-                if y >= self.height {
-                    self.done = true;
-                    points_done = Some(count + 1);
-                    break;
-                }
+                curr.powi(exponent, digits)
+            } + &self.big_c;
+            let square_length = curr.approx_complex().square_length();
+            if square_length >= self.max {
+                counts.iterations += idx as usize;
+                return (idx, smooth_iterations(idx, square_length, self.exponent));
             }
-
-            iterations +=  curr as usize;
-            if iterations - last_check > 100 {
-                last_check = iterations;
-                if performance.now() - start >= MAX_DURATION {
-                    points_done = Some(count + 1);
-                    break;
+            if self.periodicity_check {
+                if (&curr - &saved).approx_complex().square_length() < self.period_epsilon {
+                    counts.iterations += idx as usize;
+                    counts.short_circuited += 1;
+                    return inside;
+                }
+                cycle_len += 1;
+                if cycle_len == cycle_max {
+                    saved = curr.clone();
+                    cycle_len = 0;
+                    cycle_max = cycle_max.saturating_mul(2);
                 }
             }
         }
-        if let Some(points) = points_done {
-            self.res.num_points = points;
+
+        counts.iterations += self.iterations as usize;
+        inside
+    }
+
+    // deep zooms are beyond f64, their pixels are placed and iterated with arbitrary precision
+    fn iterate_at(&self, x: u32, y: u32, counts: &mut Counts) -> (u32, f32) {
+        let point = Complex::new(
+            f64::from(x).mul_add(self.scale_real, self.offset.real()),
+            f64::from(y).mul_add(self.scale_imag, self.offset.imag()),
+        );
+        if let Some(digits) = self.precision {
+            let point = (&self.origin + &BigComplex::from(point)).truncate(digits);
+            self.iterate_precise(&point, digits, counts)
         } else {
-            self.res.num_points = self.res.values.len();
+            self.iterate(&(point + self.origin_f64), counts)
         }
+    }
+}
 
-        self.x_curr = x;
-        self.y_curr = y;
+impl Fractal for JuliaSet {
+    fn calculate(&mut self, tile: &Tile, stats: Option<&mut Stats>) -> Points {
+        let start = self.clock.now();
+        let mut counts = Counts::default();
+
+        if self.subdivision && tile.block == 1 {
+            let (points, filled) = Subdivision::calculate(tile, self.iterations + 1, |x, y| {
+                if let Some((value, smooth, _)) =
+                    self.known.as_ref().and_then(|known| known.get(x, y))
+                {
+                    (value, smooth)
+                } else {
+                    self.iterate_at(x, y, &mut counts)
+                }
+            });
+            if let Some(stats) = stats {
+                stats.update(counts.iterations, points.values.len(), start, self.clock.now());
+                stats.add_filled(filled);
+                stats.add_short_circuited(counts.short_circuited);
+            }
+            return points;
+        }
+
+        let mut points = Points::new(*tile);
+        for idx in 0..tile.num_points() {
+            let (x, y) = tile.position(idx);
+            let (value, smooth) = if let Some((value, smooth, _)) =
+                self.known.as_ref().and_then(|known| known.get(x, y))
+            {
+                (value, smooth)
+            } else {
+                self.iterate_at(x, y, &mut counts)
+            };
+            points.values[idx] = value;
+            points.smooth[idx] = smooth;
+        }
 
         if let Some(stats) = stats {
-            stats.update(iterations, self.res.num_points, start);
+            stats.update(counts.iterations, points.values.len(), start, self.clock.now());
+            stats.add_short_circuited(counts.short_circuited);
         }
 
-        &self.res
+        points
+    }
+
+    fn set_known_points(&mut self, known: KnownPoints) {
+        self.known = Some(known);
     }
 
-    fn is_done(&self) -> bool {
-        self.done
+    fn set_renderer(&mut self, renderer: Renderer) {
+        self.subdivision = renderer == Renderer::Subdivision;
     }
 }
@@ -7,19 +7,49 @@ use seed::{prelude::*, *};
 
 use serde::{Deserialize, Serialize};
 
+mod bigfloat;
+pub mod clock;
+mod coloring;
+use bigfloat::BigComplex;
 mod complex;
 use complex::Complex;
 
+mod editor;
+use editor::Editor;
+
 mod fractal;
-use fractal::Fractal;
+use fractal::{Fractal, IterationBuffer};
+
+mod history;
+use history::Histories;
+
+mod link;
 
+mod migration;
+use migration::CONFIG_VERSION;
+
+pub mod batch;
+mod bookmarks;
+use bookmarks::Bookmarks;
+mod burning_ship;
+mod export;
 mod julia_set;
 mod mandelbrot;
+mod newton;
+mod palette;
+use palette::Palette;
+mod polynomial;
+mod render;
+mod scheduler;
+use scheduler::Scheduler;
 mod stats;
 use stats::Stats;
+mod subdivision;
+mod validation;
+mod workers;
+use workers::WorkerPool;
 
 pub mod util;
-// use util::{get_f64_from_input, get_u32_from_input};
 
 mod canvas;
 
@@ -28,9 +58,19 @@ use views::view;
 
 mod event_handler;
 use event_handler::{
-    on_msg_cancel_edit, on_msg_draw, on_msg_edit, on_msg_mouse_down, on_msg_mouse_move,
+    on_msg_cancel_edit, on_msg_input_changed, on_msg_draw, on_msg_edit, on_msg_mouse_down, on_msg_mouse_move,
     on_msg_mouse_up, on_msg_save_edit, on_msg_start, on_msg_clear, on_msg_type_changed,
-    on_msg_reset_area, on_msg_reset_params, on_msg_zoom_out_area, on_msg_stats_changed
+    on_msg_reset_area, on_msg_reset_params, on_msg_zoom_out_area, on_msg_stats_changed, on_msg_smooth_changed,
+    on_msg_progressive_changed, on_msg_renderer_changed, on_msg_cardioid_check_changed,
+    on_msg_periodicity_check_changed, on_msg_stop, on_msg_tile_done, on_msg_worker_failed,
+    adjust_height_to_ratio, on_msg_zoom_factor_changed, on_msg_wheel, on_msg_back, on_msg_forward, on_msg_key_down,
+    on_msg_add_bookmark, on_msg_restore_bookmark, on_msg_rename_bookmark, on_msg_delete_bookmark,
+    on_msg_export_bookmarks, on_msg_import_bookmarks, on_msg_bookmarks_imported,
+    on_msg_recolor, on_msg_export_png, on_msg_import_png, on_msg_png_imported,
+    on_msg_copy_to_clipboard, on_msg_clipboard_result,
+    on_msg_edit_palette, on_msg_save_palette, on_msg_cancel_palette,
+    on_msg_reset_palette, on_msg_add_palette_stop, on_msg_remove_palette_stop,
+    on_msg_palette_stop_color, on_msg_palette_stop_position, on_msg_palette_interpolation
 };
 
 use canvas::Canvas;
@@ -39,17 +79,49 @@ use web_sys::{ImageData};
 const JULIA_DEFAULT_X: (f64, f64) = (1.5, 1.0);
 const JULIA_DEFAULT_C: (f64, f64) = (-0.8, 0.156);
 const JULIA_DEFAULT_ITERATIONS: u32 = 400;
+const JULIA_DEFAULT_EXPONENT: f64 = 2.0;
 
 const MANDELBROT_DEFAULT_C_MAX: (f64, f64) = (0.47, 1.12);
 const MANDELBROT_DEFAULT_C_MIN: (f64, f64) = (-2.00, -1.12);
 const MANDELBROT_DEFAULT_ITERATIONS: u32 = 400;
+const MANDELBROT_DEFAULT_EXPONENT: f64 = 2.0;
+
+const BURNING_SHIP_DEFAULT_C_MAX: (f64, f64) = (1.5, 1.0);
+const BURNING_SHIP_DEFAULT_C_MIN: (f64, f64) = (-2.5, -2.0);
+const BURNING_SHIP_DEFAULT_ITERATIONS: u32 = 400;
+
+const NEWTON_DEFAULT_X: (f64, f64) = (1.5, 1.0);
+const NEWTON_DEFAULT_COEFFICIENTS: [f64; 4] = [1.0, 0.0, 0.0, -1.0];
+const NEWTON_DEFAULT_ITERATIONS: u32 = 100;
+const NEWTON_MIN_DEGREE: usize = 2;
+// root colors are spread over the hue range, more roots would be hard to tell apart
+const NEWTON_MAX_DEGREE: usize = 12;
+
+// upper bound for the iterations entered in the editor
+const MAX_ITERATIONS: u32 = 100_000;
+// the area height must be within 1 / MAX_ASPECT_RATIO to MAX_ASPECT_RATIO times its width
+const MAX_ASPECT_RATIO: f64 = 8.0;
+
+// the escape radius estimate in util::find_escape_radius only holds for exponents >= 2
+const MIN_EXPONENT: f64 = 2.0;
+
+// area scale applied per mouse wheel step
+const ZOOM_DEFAULT_FACTOR: f64 = 1.5;
 
 const DEFAULT_WIDTH: u32 = 1024;
 const DEFAULT_HEIGHT: u32 = 800;
 
 const ENTER_KEY: &str = "Enter";
 const BACKGROUND_COLOR: &str = "#000000";
-const STORAGE_KEY: &str = "seed_fractals_v1";
+const STORAGE_KEY: &str = "seed_fractals_config";
+const HISTORY_STORAGE_KEY: &str = "seed_fractals_history_v1";
+// a history that could not be read is kept here instead of being overwritten
+const HISTORY_BACKUP_KEY: &str = "seed_fractals_history_backup";
+const BOOKMARKS_STORAGE_KEY: &str = "seed_fractals_bookmarks_v1";
+// a bookmark library that could not be read is kept here instead of being overwritten
+const BOOKMARKS_BACKUP_KEY: &str = "seed_fractals_bookmarks_backup";
+
+const THUMBNAIL_WIDTH: u32 = 96;
 
 const MAX_DURATION: f64 = 200.0;
 
@@ -58,66 +130,168 @@ const MAX_DURATION: f64 = 200.0;
 // ------ ------
 
 // `init` describes what should happen when your app started.
-fn init(_: Url, orders: &mut impl Orders<Msg>) -> Model {
+#[allow(clippy::needless_pass_by_value)]
+fn init(url: Url, orders: &mut impl Orders<Msg>) -> Model {
     orders.after_next_render(|_| Msg::Clear);
-    Model {
+    let mut status_text = String::new();
+    let mut config = match migration::load_config() {
+        Ok(config) => config.unwrap_or_default(),
+        Err(err) => {
+            log!(format!("failed to load stored config: {}", err));
+            status_text = "Stored settings could not be loaded, using defaults".to_string();
+            Config::default()
+        }
+    };
+    let history = match migration::load_stored(HISTORY_STORAGE_KEY, HISTORY_BACKUP_KEY, |value| {
+        serde_json::from_value(value).map_err(|err| format!("invalid history: {err}"))
+    }) {
+        Ok(history) => history.unwrap_or_default(),
+        Err(err) => {
+            log!(format!("failed to load stored history: {}", err));
+            status_text = "Stored navigation history could not be loaded, starting anew".to_string();
+            Histories::default()
+        }
+    };
+    let bookmarks =
+        match migration::load_stored(BOOKMARKS_STORAGE_KEY, BOOKMARKS_BACKUP_KEY, Bookmarks::migrate) {
+            Ok(bookmarks) => bookmarks.unwrap_or_default(),
+            Err(err) => {
+                log!(format!("failed to load stored bookmarks: {}", err));
+                status_text = "Stored bookmarks could not be loaded, a backup was kept".to_string();
+                Bookmarks::default()
+            }
+        };
+    // a deep link takes precedence over the stored view
+    if let Some(hash) = url.hash() {
+        match link::apply(&mut config, hash) {
+            Ok(()) => {
+                orders.after_next_render(|_| Msg::Start);
+            }
+            Err(err) => log!(format!("ignoring invalid link: {}", err)),
+        }
+    }
+
+    orders.perform_cmd(async { Msg::ClipboardPermission(export::clipboard_write_denied().await) });
+    orders.stream(streams::window_event(Ev::KeyDown, |event| {
+        Msg::KeyDown(event.unchecked_into())
+    }));
+    let mut model = Model {
         width: DEFAULT_WIDTH,
         height: DEFAULT_HEIGHT,
-        config: LocalStorage::get(STORAGE_KEY).unwrap_or_default(),
+        config,
+        history,
+        bookmarks,
+        bookmark_name: String::new(),
+        view_bookmarks: false,
+        clipboard_denied: false,
         background_color: BACKGROUND_COLOR.to_string(),
         canvas: None,
         fractal: None,
+        scheduler: None,
+        render_key: String::new(),
+        workers: WorkerPool::new(&*orders),
+        buffer: IterationBuffer::new(DEFAULT_WIDTH, DEFAULT_HEIGHT),
         mouse_drag: None,
         paused: true,
         edit_mode: false,
-        stats_text: "".to_string(),
-        stats: None    
-    }
+        palette_edit: None,
+        editor: Editor::default(),
+        stats_text: String::new(),
+        stats: None,
+        status_text,
+    };
+    adjust_height_to_ratio(&mut model);
+    model
 }
 
 // ------ ------
 //     Model
 // ------ ------
 
+#[allow(clippy::struct_excessive_bools)]
 pub struct Model {
     width: u32,
     height: u32,
     config: Config,
+    history: Histories,
+    bookmarks: Bookmarks,
+    bookmark_name: String,
+    view_bookmarks: bool,
+    // the clipboard-write permission was denied, copying the image is disabled
+    clipboard_denied: bool,
     background_color: String,
     canvas: Option<Canvas>,
     fractal: Option<Box<dyn Fractal>>,
+    scheduler: Option<Scheduler>,
+    // config and size of the current render, see event_handler::on_msg_start
+    render_key: String,
+    // calculates the image in parallel, None if web workers are not available
+    workers: Option<WorkerPool>,
+    buffer: IterationBuffer,
     mouse_drag: Option<MouseDrag>,
     paused: bool,
     edit_mode: bool,
+    palette_edit: Option<Palette>,
+    editor: Editor,
     stats_text: String,
-    stats: Option<Stats>
+    stats: Option<Stats>,
+    status_text: String,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
+#[allow(clippy::struct_excessive_bools, clippy::struct_field_names)]
 struct Config {
+    version: u32,
     view_stats: bool,
+    smooth_coloring: bool,
+    // render coarse previews before the full resolution image
+    progressive: bool,
+    renderer: Renderer,
+    // skip points of the main cardioid and period-2 bulb, Mandelbrot set with exponent 2 only
+    cardioid_check: bool,
+    // stop iterating periodic orbits, Mandelbrot and Julia sets
+    periodicity_check: bool,
+    zoom_factor: f64,
     active_config: FractalType,
     julia_set_cfg: JuliaSetCfg,
     mandelbrot_cfg: MandelbrotCfg,
+    burning_ship_cfg: BurningShipCfg,
+    newton_cfg: NewtonCfg,
+    palette: Palette,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
+            version: CONFIG_VERSION,
             view_stats: false,
+            smooth_coloring: true,
+            progressive: true,
+            renderer: Renderer::Scanline,
+            cardioid_check: true,
+            periodicity_check: true,
+            zoom_factor: ZOOM_DEFAULT_FACTOR,
             active_config: FractalType::Mandelbrot,
             julia_set_cfg: JuliaSetCfg::default(),
-            mandelbrot_cfg: MandelbrotCfg::default()
+            mandelbrot_cfg: MandelbrotCfg::default(),
+            burning_ship_cfg: BurningShipCfg::default(),
+            newton_cfg: NewtonCfg::default(),
+            palette: Palette::default()
         }
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 struct JuliaSetCfg {
     max_iterations: u32,
     x_max: Complex,
     x_min: Complex,
+    // deep zooms keep their centre here, x_min and x_max are relative to it,
+    // bookmarks and history entries of older versions have none
+    #[serde(default)]
+    origin: BigComplex,
     c: Complex,
+    exponent: f64,
 }
 
 impl Default for JuliaSetCfg {
@@ -126,24 +300,69 @@ impl Default for JuliaSetCfg {
             max_iterations: JULIA_DEFAULT_ITERATIONS,
             x_max: Complex::new(JULIA_DEFAULT_X.0, JULIA_DEFAULT_X.1),
             x_min: Complex::new(-JULIA_DEFAULT_X.0, -JULIA_DEFAULT_X.1),
+            origin: BigComplex::default(),
             c: Complex::new(JULIA_DEFAULT_C.0, JULIA_DEFAULT_C.1),
+            exponent: JULIA_DEFAULT_EXPONENT,
         }
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 struct MandelbrotCfg {
     max_iterations: u32,
     c_max: Complex,
     c_min: Complex,
+    // deep zooms keep their centre here, c_min and c_max are relative to it,
+    // bookmarks and history entries of older versions have none
+    #[serde(default)]
+    origin: BigComplex,
+    exponent: f64,
 }
 
 impl Default for MandelbrotCfg {
     fn default() -> Self {
         Self {
-            max_iterations: 400,
+            max_iterations: MANDELBROT_DEFAULT_ITERATIONS,
             c_max: Complex::new(MANDELBROT_DEFAULT_C_MAX.0, MANDELBROT_DEFAULT_C_MAX.1),
             c_min: Complex::new(MANDELBROT_DEFAULT_C_MIN.0, MANDELBROT_DEFAULT_C_MIN.1),
+            origin: BigComplex::default(),
+            exponent: MANDELBROT_DEFAULT_EXPONENT,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct BurningShipCfg {
+    max_iterations: u32,
+    c_max: Complex,
+    c_min: Complex,
+}
+
+impl Default for BurningShipCfg {
+    fn default() -> Self {
+        Self {
+            max_iterations: BURNING_SHIP_DEFAULT_ITERATIONS,
+            c_max: Complex::new(BURNING_SHIP_DEFAULT_C_MAX.0, BURNING_SHIP_DEFAULT_C_MAX.1),
+            c_min: Complex::new(BURNING_SHIP_DEFAULT_C_MIN.0, BURNING_SHIP_DEFAULT_C_MIN.1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct NewtonCfg {
+    max_iterations: u32,
+    x_max: Complex,
+    x_min: Complex,
+    coefficients: Vec<f64>,
+}
+
+impl Default for NewtonCfg {
+    fn default() -> Self {
+        Self {
+            max_iterations: NEWTON_DEFAULT_ITERATIONS,
+            x_max: Complex::new(NEWTON_DEFAULT_X.0, NEWTON_DEFAULT_X.1),
+            x_min: Complex::new(-NEWTON_DEFAULT_X.0, -NEWTON_DEFAULT_X.1),
+            coefficients: NEWTON_DEFAULT_COEFFICIENTS.to_vec(),
         }
     }
 }
@@ -152,12 +371,36 @@ impl Default for MandelbrotCfg {
 enum FractalType {
     Mandelbrot,
     JuliaSet,
+    BurningShip,
+    Newton,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+enum Renderer {
+    // every point is iterated, row by row or progressively
+    Scanline,
+    // uniform rectangles are filled without iterating, see subdivision.rs
+    Subdivision,
 }
 
 struct MouseDrag {
     start: (u32, u32),
     curr: (u32, u32),
     image_data: Option<ImageData>,
+    // dragging moves the image instead of selecting a rectangle
+    pan: bool,
+    // the render stopped when panning started, it continues if the image was not moved
+    resume: bool,
+}
+
+impl MouseDrag {
+    #[allow(clippy::cast_possible_wrap)]
+    const fn offset(&self) -> (i32, i32) {
+        (
+            self.curr.0 as i32 - self.start.0 as i32,
+            self.curr.1 as i32 - self.start.1 as i32,
+        )
+    }
 }
 
 // ------ ------
@@ -169,20 +412,59 @@ pub enum Msg {
     Start,
     Stop,
     Clear,
-    TypeChanged,
+    Recolor,
+    ExportPng,
+    CopyToClipboard,
+    ClipboardPermission(bool),
+    ClipboardResult(Result<(), String>),
+    TypeChanged(String),
     Edit,
     SaveEdit,
     CancelEdit,
-    StatsChanged,
+    InputChanged(&'static str, String),
+    StatsChanged(bool),
+    SmoothChanged(bool),
+    ProgressiveChanged(bool),
+    RendererChanged(String),
+    CardioidCheckChanged(bool),
+    PeriodicityCheckChanged(bool),
+    ZoomFactorChanged(String),
+    EditPalette,
+    SavePalette,
+    CancelPalette,
+    ResetPalette,
+    AddPaletteStop,
+    RemovePaletteStop(usize),
+    PaletteStopColor(usize, String),
+    PaletteStopPosition(usize, String),
+    PaletteInterpolation(String),
     Draw,
+    TileDone(JsValue),
+    WorkerFailed(String),
     ResetParams,
     ResetArea,
     ZoomOutArea,
     MouseDown(web_sys::MouseEvent),
     MouseMove(web_sys::MouseEvent),
     MouseUp(Option<web_sys::MouseEvent>),
+    Wheel(web_sys::WheelEvent),
+    Back,
+    Forward,
+    KeyDown(web_sys::KeyboardEvent),
+    ToggleBookmarks,
+    BookmarkName(String),
+    AddBookmark,
+    RestoreBookmark(usize),
+    RenameBookmark(usize, String),
+    DeleteBookmark(usize),
+    ExportBookmarks,
+    ImportBookmarks(Option<web_sys::File>),
+    BookmarksImported(Result<String, String>),
+    ImportPng(Option<web_sys::File>),
+    PngImported(Result<Vec<u8>, String>),
 }
 
+#[allow(clippy::too_many_lines)]
 fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
     match msg {
         Msg::Start => {
@@ -191,15 +473,35 @@ fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
         }
         Msg::Stop => {
             log!("Message received: Stop");
-            model.paused = true;
+            on_msg_stop(model);
         }
         Msg::Clear => {
             log!("Message received: Clear");
             on_msg_clear(model);            
         }
-        Msg::TypeChanged => {
+        Msg::Recolor => {
+            log!("Message received: Recolor");
+            on_msg_recolor(model);
+        }
+        Msg::ExportPng => {
+            log!("Message received: ExportPng");
+            on_msg_export_png(model);
+        }
+        Msg::CopyToClipboard => {
+            log!("Message received: CopyToClipboard");
+            on_msg_copy_to_clipboard(model, orders);
+        }
+        Msg::ClipboardPermission(denied) => {
+            log!("Message received: ClipboardPermission");
+            model.clipboard_denied = denied;
+        }
+        Msg::ClipboardResult(result) => {
+            log!("Message received: ClipboardResult");
+            on_msg_clipboard_result(model, result);
+        }
+        Msg::TypeChanged(value) => {
             log!("Message received: TypeChanged");
-            on_msg_type_changed(model);
+            on_msg_type_changed(model, &value);
         }
 
         Msg::Edit => {
@@ -215,6 +517,10 @@ fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
             log!("Message received: SaveEdit");
             on_msg_cancel_edit(model);
         }
+        Msg::InputChanged(field, value) => {
+            log!(format!("Message received: InputChanged({}, {})", field, value));
+            on_msg_input_changed(model, field, value);
+        }
         Msg::ResetParams => {
             log!("Message received: ResetParams");
             on_msg_reset_params(model);
@@ -227,14 +533,80 @@ fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
             log!("Message received: ZoomOutArea");
             on_msg_zoom_out_area(model);
         },
-        Msg::StatsChanged => {
+        Msg::StatsChanged(checked) => {
             log!("Message received: StatsChanged");
-            on_msg_stats_changed(model);
+            on_msg_stats_changed(model, checked);
+        },
+        Msg::SmoothChanged(checked) => {
+            log!("Message received: SmoothChanged");
+            on_msg_smooth_changed(model, checked);
+        },
+        Msg::ProgressiveChanged(checked) => {
+            log!("Message received: ProgressiveChanged");
+            on_msg_progressive_changed(model, checked);
+        },
+        Msg::RendererChanged(value) => {
+            log!("Message received: RendererChanged");
+            on_msg_renderer_changed(model, &value);
         },
+        Msg::CardioidCheckChanged(checked) => {
+            log!("Message received: CardioidCheckChanged");
+            on_msg_cardioid_check_changed(model, checked);
+        },
+        Msg::PeriodicityCheckChanged(checked) => {
+            log!("Message received: PeriodicityCheckChanged");
+            on_msg_periodicity_check_changed(model, checked);
+        },
+        Msg::ZoomFactorChanged(value) => {
+            log!("Message received: ZoomFactorChanged");
+            on_msg_zoom_factor_changed(model, &value);
+        },
+        Msg::EditPalette => {
+            log!("Message received: EditPalette");
+            on_msg_edit_palette(model);
+        }
+        Msg::SavePalette => {
+            log!("Message received: SavePalette");
+            on_msg_save_palette(model);
+        }
+        Msg::CancelPalette => {
+            log!("Message received: CancelPalette");
+            on_msg_cancel_palette(model);
+        }
+        Msg::ResetPalette => {
+            log!("Message received: ResetPalette");
+            on_msg_reset_palette(model);
+        }
+        Msg::AddPaletteStop => {
+            log!("Message received: AddPaletteStop");
+            on_msg_add_palette_stop(model);
+        }
+        Msg::RemovePaletteStop(idx) => {
+            log!("Message received: RemovePaletteStop");
+            on_msg_remove_palette_stop(model, idx);
+        }
+        Msg::PaletteStopColor(idx, value) => {
+            on_msg_palette_stop_color(model, idx, &value);
+        }
+        Msg::PaletteStopPosition(idx, value) => {
+            on_msg_palette_stop_position(model, idx, &value);
+        }
+        Msg::PaletteInterpolation(value) => {
+            log!("Message received: PaletteInterpolation");
+            on_msg_palette_interpolation(model, &value);
+        }
         Msg::Draw => {
             // log!("Message received: Draw");
             on_msg_draw(model, orders);
         }
+        Msg::TileDone(data) => {
+            // log!("Message received: TileDone");
+            on_msg_tile_done(model, &data, orders);
+        }
+        Msg::WorkerFailed(message) => {
+            log!(format!("Message received: WorkerFailed({})", message));
+            on_msg_worker_failed(model, orders);
+        }
         Msg::MouseDown(ev) => {
             log!("Message received: MouseDown");
             on_msg_mouse_down(model, &ev);
@@ -245,7 +617,65 @@ fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
         }
         Msg::MouseUp(ev) => {
             log!("Message received: MouseUp");
-            on_msg_mouse_up(model, ev);
+            on_msg_mouse_up(model, ev, orders);
+        }
+        Msg::Wheel(ev) => {
+            log!("Message received: Wheel");
+            on_msg_wheel(model, &ev, orders);
+        }
+        Msg::Back => {
+            log!("Message received: Back");
+            on_msg_back(model, orders);
+        }
+        Msg::Forward => {
+            log!("Message received: Forward");
+            on_msg_forward(model, orders);
+        }
+        Msg::KeyDown(ev) => {
+            on_msg_key_down(model, &ev, orders);
+        }
+        Msg::ToggleBookmarks => {
+            log!("Message received: ToggleBookmarks");
+            model.view_bookmarks = !model.view_bookmarks;
+        }
+        Msg::BookmarkName(name) => {
+            model.bookmark_name = name;
+        }
+        Msg::AddBookmark => {
+            log!("Message received: AddBookmark");
+            on_msg_add_bookmark(model);
+        }
+        Msg::RestoreBookmark(idx) => {
+            log!("Message received: RestoreBookmark");
+            on_msg_restore_bookmark(model, idx, orders);
+        }
+        Msg::RenameBookmark(idx, name) => {
+            log!("Message received: RenameBookmark");
+            on_msg_rename_bookmark(model, idx, &name);
+        }
+        Msg::DeleteBookmark(idx) => {
+            log!("Message received: DeleteBookmark");
+            on_msg_delete_bookmark(model, idx);
+        }
+        Msg::ExportBookmarks => {
+            log!("Message received: ExportBookmarks");
+            on_msg_export_bookmarks(model);
+        }
+        Msg::ImportBookmarks(file) => {
+            log!("Message received: ImportBookmarks");
+            on_msg_import_bookmarks(model, file, orders);
+        }
+        Msg::BookmarksImported(result) => {
+            log!("Message received: BookmarksImported");
+            on_msg_bookmarks_imported(model, result);
+        }
+        Msg::ImportPng(file) => {
+            log!("Message received: ImportPng");
+            on_msg_import_png(model, file, orders);
+        }
+        Msg::PngImported(result) => {
+            log!("Message received: PngImported");
+            on_msg_png_imported(model, result, orders);
         }
     }
 }
@@ -264,6 +694,10 @@ fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
 #[allow(clippy::unused_unit)]
 #[wasm_bindgen(start)]
 pub fn start() {
+    // the web workers load this module too, they only call workers::handle_message
+    if web_sys::window().is_none() {
+        return;
+    }
     // Mount the `app` to the element with the `id` "app".
     App::start("app", init, update, view);
 }
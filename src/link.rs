@@ -0,0 +1,347 @@
+use super::{
+    bigfloat::{BigComplex, BigFloat},
+    complex::Complex,
+    palette::{html_to_rgba, rgb_to_html, ColorStop, Interpolation, Palette},
+    polynomial::{parse_coefficients, Polynomial},
+    util::{format_coordinate, set_area},
+    validation, Config, FractalType, MIN_EXPONENT, NEWTON_MAX_DEGREE, NEWTON_MIN_DEGREE,
+};
+
+// Deep links describe the active view in the url hash, e.g.
+// #type=julia&min=-1.5,-1&max=1.5,1&iter=400&exp=2&c=-0.8,0.156&palette=hsl;0:FF0000;1:FF00FF
+// the corners of deep zooms carry all the decimal places telling their pixels apart
+
+const fn type_name(fractal_type: FractalType) -> &'static str {
+    match fractal_type {
+        FractalType::Mandelbrot => "mandelbrot",
+        FractalType::JuliaSet => "julia",
+        FractalType::BurningShip => "burning_ship",
+        FractalType::Newton => "newton",
+    }
+}
+
+fn format_complex(value: Complex) -> String {
+    format!("{},{}", value.real(), value.imag())
+}
+
+// corners of the area `min` to `max` relative to `origin`
+fn format_area(origin: &BigComplex, min: Complex, max: Complex) -> (String, String) {
+    let width = f64::min(max.real() - min.real(), max.imag() - min.imag());
+    let format = |value: Complex| {
+        format!(
+            "{},{}",
+            format_coordinate(origin.real(), value.real(), width),
+            format_coordinate(origin.imag(), value.imag(), width)
+        )
+    };
+    (format(min), format(max))
+}
+
+fn parse_coordinates(value: &str) -> Result<BigComplex, String> {
+    let mut parts = value.split(',').map(str::parse::<BigFloat>);
+    if let (Some(Ok(real)), Some(Ok(imag)), None) = (parts.next(), parts.next(), parts.next()) {
+        let res = BigComplex::new(real, imag);
+        if res.to_complex().square_length().is_finite() {
+            return Ok(res);
+        }
+    }
+    Err(format!("invalid complex number: {value}"))
+}
+
+fn parse_complex(value: &str) -> Result<Complex, String> {
+    let mut parts = value.split(',').map(str::parse::<f64>);
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(Ok(real)), Some(Ok(imag)), None) if real.is_finite() && imag.is_finite() => {
+            Ok(Complex::new(real, imag))
+        }
+        _ => Err(format!("invalid complex number: {value}")),
+    }
+}
+
+fn format_palette(palette: &Palette) -> String {
+    let mut res = match palette.interpolation {
+        Interpolation::Rgb => "rgb".to_string(),
+        Interpolation::Hsl => "hsl".to_string(),
+    };
+    palette.stops.iter().for_each(|stop| {
+        res.push_str(format!(";{}:{}", stop.position, &rgb_to_html(stop.color)[1..]).as_str());
+    });
+    res
+}
+
+fn parse_palette(value: &str) -> Result<Palette, String> {
+    let invalid = || format!("invalid palette: {value}");
+    let mut parts = value.split(';');
+    let interpolation = match parts.next() {
+        Some("rgb") => Interpolation::Rgb,
+        Some("hsl") => Interpolation::Hsl,
+        _ => return Err(invalid()),
+    };
+    let stops = parts
+        .map(|stop| {
+            let (position, color) = stop.split_once(':').ok_or_else(invalid)?;
+            let position = position.parse::<f32>().map_err(|_| invalid())?;
+            if color.len() != 6 || !color.chars().all(|ch| ch.is_ascii_hexdigit()) {
+                return Err(invalid());
+            }
+            let color = html_to_rgba(format!("#{color}").as_str());
+            Ok(ColorStop {
+                position,
+                color: [color[0], color[1], color[2]],
+            })
+        })
+        .collect::<Result<Vec<ColorStop>, String>>()?;
+
+    let mut palette = Palette {
+        interpolation,
+        stops,
+    };
+    palette.sort();
+    if palette.is_valid() {
+        Ok(palette)
+    } else {
+        Err(invalid())
+    }
+}
+
+fn parse_iterations(value: &str) -> Result<u32, String> {
+    match value.parse::<u32>() {
+        Ok(iterations) if iterations > 0 => Ok(iterations),
+        _ => Err(format!("invalid iterations: {value}")),
+    }
+}
+
+fn parse_exponent(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(exponent) if exponent.is_finite() && exponent >= MIN_EXPONENT => Ok(exponent),
+        _ => Err(format!("invalid exponent: {value}")),
+    }
+}
+
+/// Encode the active fractal and the palette as url hash.
+pub fn encode(config: &Config) -> String {
+    let mut params = vec![("type", type_name(config.active_config).to_string())];
+    match config.active_config {
+        FractalType::JuliaSet => {
+            let cfg = &config.julia_set_cfg;
+            let (min, max) = format_area(&cfg.origin, cfg.x_min, cfg.x_max);
+            params.push(("min", min));
+            params.push(("max", max));
+            params.push(("iter", cfg.max_iterations.to_string()));
+            params.push(("exp", cfg.exponent.to_string()));
+            params.push(("c", format_complex(cfg.c)));
+        }
+        FractalType::Mandelbrot => {
+            let cfg = &config.mandelbrot_cfg;
+            let (min, max) = format_area(&cfg.origin, cfg.c_min, cfg.c_max);
+            params.push(("min", min));
+            params.push(("max", max));
+            params.push(("iter", cfg.max_iterations.to_string()));
+            params.push(("exp", cfg.exponent.to_string()));
+        }
+        FractalType::BurningShip => {
+            let cfg = &config.burning_ship_cfg;
+            params.push(("min", format_complex(cfg.c_min)));
+            params.push(("max", format_complex(cfg.c_max)));
+            params.push(("iter", cfg.max_iterations.to_string()));
+        }
+        FractalType::Newton => {
+            let cfg = &config.newton_cfg;
+            params.push(("min", format_complex(cfg.x_min)));
+            params.push(("max", format_complex(cfg.x_max)));
+            params.push(("iter", cfg.max_iterations.to_string()));
+            params.push((
+                "coef",
+                cfg.coefficients
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<String>>()
+                    .join(","),
+            ));
+        }
+    }
+    params.push(("palette", format_palette(&config.palette)));
+
+    params
+        .iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect::<Vec<String>>()
+        .join("&")
+}
+
+/// Apply the view described by `hash` on top of `config`.
+/// Parameters missing from the link keep their value, on error `config` is left unchanged.
+pub fn apply(config: &mut Config, hash: &str) -> Result<(), String> {
+    let mut res = config.clone();
+    let params = hash
+        .split('&')
+        .filter(|param| !param.is_empty())
+        .map(|param| {
+            param
+                .split_once('=')
+                .ok_or_else(|| format!("invalid parameter: {param}"))
+        })
+        .collect::<Result<Vec<(&str, &str)>, String>>()?;
+
+    // the type decides which config the other parameters belong to, the last one counts
+    if let Some((_, value)) = params.iter().rfind(|(key, _)| *key == "type") {
+        res.active_config = match *value {
+            "mandelbrot" => FractalType::Mandelbrot,
+            "julia" => FractalType::JuliaSet,
+            "burning_ship" => FractalType::BurningShip,
+            "newton" => FractalType::Newton,
+            _ => return Err(format!("invalid fractal type: {value}")),
+        };
+    }
+
+    // missing corners keep their value
+    let (mut area_min, mut area_max) = (None, None);
+    for (key, value) in params {
+        match (key, res.active_config) {
+            ("type", _) => (),
+            ("palette", _) => res.palette = parse_palette(value)?,
+            ("min", _) => area_min = Some(parse_coordinates(value)?),
+            ("max", _) => area_max = Some(parse_coordinates(value)?),
+            ("iter", FractalType::JuliaSet) => {
+                res.julia_set_cfg.max_iterations = parse_iterations(value)?;
+            }
+            ("exp", FractalType::JuliaSet) => res.julia_set_cfg.exponent = parse_exponent(value)?,
+            ("c", FractalType::JuliaSet) => res.julia_set_cfg.c = parse_complex(value)?,
+            ("iter", FractalType::Mandelbrot) => {
+                res.mandelbrot_cfg.max_iterations = parse_iterations(value)?;
+            }
+            ("exp", FractalType::Mandelbrot) => res.mandelbrot_cfg.exponent = parse_exponent(value)?,
+            ("iter", FractalType::BurningShip) => {
+                res.burning_ship_cfg.max_iterations = parse_iterations(value)?;
+            }
+            ("iter", FractalType::Newton) => res.newton_cfg.max_iterations = parse_iterations(value)?,
+            ("coef", FractalType::Newton) => {
+                let coefficients = parse_coefficients(value)
+                    .ok_or_else(|| format!("invalid coefficients: {value}"))?;
+                let degree = Polynomial::new(&coefficients).degree();
+                if !(NEWTON_MIN_DEGREE..=NEWTON_MAX_DEGREE).contains(&degree) {
+                    return Err(format!("invalid polynomial degree: {degree}"));
+                }
+                res.newton_cfg.coefficients = coefficients;
+            }
+            _ => return Err(format!("unexpected parameter: {key}")),
+        }
+    }
+
+    if area_min.is_some() || area_max.is_some() {
+        match res.active_config {
+            FractalType::JuliaSet => {
+                let cfg = &mut res.julia_set_cfg;
+                let abs_min = area_min.unwrap_or_else(|| &cfg.origin + &cfg.x_min.into());
+                let abs_max = area_max.unwrap_or_else(|| &cfg.origin + &cfg.x_max.into());
+                set_area(&mut cfg.origin, &mut cfg.x_min, &mut cfg.x_max, &abs_min, &abs_max);
+            }
+            FractalType::Mandelbrot => {
+                let cfg = &mut res.mandelbrot_cfg;
+                let abs_min = area_min.unwrap_or_else(|| &cfg.origin + &cfg.c_min.into());
+                let abs_max = area_max.unwrap_or_else(|| &cfg.origin + &cfg.c_max.into());
+                set_area(&mut cfg.origin, &mut cfg.c_min, &mut cfg.c_max, &abs_min, &abs_max);
+            }
+            FractalType::BurningShip => {
+                let cfg = &mut res.burning_ship_cfg;
+                cfg.c_min = area_min.map_or(cfg.c_min, |min| min.to_complex());
+                cfg.c_max = area_max.map_or(cfg.c_max, |max| max.to_complex());
+            }
+            FractalType::Newton => {
+                let cfg = &mut res.newton_cfg;
+                cfg.x_min = area_min.map_or(cfg.x_min, |min| min.to_complex());
+                cfg.x_max = area_max.map_or(cfg.x_max, |max| max.to_complex());
+            }
+        }
+    }
+
+    // bounds the canvas height and the work of rendering a crafted link
+    validation::check(&res, res.active_config)?;
+
+    *config = res;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{apply, encode};
+    use crate::{complex::Complex, palette::Interpolation, Config, FractalType};
+
+    #[test]
+    fn test_round_trip() {
+        let mut config = Config {
+            active_config: FractalType::JuliaSet,
+            ..Config::default()
+        };
+        config.julia_set_cfg.c = Complex::new(-0.123_456_789, 0.1);
+        config.julia_set_cfg.x_min = Complex::new(-0.5, -0.25);
+        config.julia_set_cfg.max_iterations = 1234;
+        config.palette.interpolation = Interpolation::Rgb;
+        let hash = encode(&config);
+
+        let mut res = Config::default();
+        apply(&mut res, hash.as_str()).expect("failed to apply link");
+        assert_eq!(res.active_config, FractalType::JuliaSet);
+        assert_eq!(res.julia_set_cfg, config.julia_set_cfg);
+        assert_eq!(res.palette, config.palette);
+        assert_eq!(encode(&res), hash);
+    }
+
+    #[test]
+    fn test_deep_zoom() {
+        let min = "-0.743643887037158704752191506114774,0.131825904205311970493132056385139";
+        let max = "-0.743643887037158704752191506104774,0.131825904205311970493132056395139";
+        let mut config = Config::default();
+        apply(&mut config, format!("type=mandelbrot&min={}&max={}", min, max).as_str())
+            .expect("failed to apply link");
+        let cfg = &config.mandelbrot_cfg;
+        assert!(cfg.c_max.real() - cfg.c_min.real() < 1e-28);
+        assert!(!cfg.origin.is_zero());
+
+        let hash = encode(&config);
+        assert!(hash.contains(format!("min={}&max={}", min, max).as_str()));
+        let mut res = Config::default();
+        apply(&mut res, hash.as_str()).expect("failed to apply link");
+        assert_eq!(res.mandelbrot_cfg, config.mandelbrot_cfg);
+
+        // a single corner moves relative to the other one
+        apply(&mut res, "max=-0.74364388703715870475219150,0.13182590420531197049313206")
+            .expect("failed to apply link");
+        assert!(res.mandelbrot_cfg.c_max.real() - res.mandelbrot_cfg.c_min.real() > 1e-28);
+    }
+
+    #[test]
+    fn test_partial_link() {
+        let mut config = Config::default();
+        apply(&mut config, "type=newton&coef=1,0,-1&iter=50").expect("failed to apply link");
+        assert_eq!(config.active_config, FractalType::Newton);
+        assert_eq!(config.newton_cfg.coefficients, vec![1.0, 0.0, -1.0]);
+        assert_eq!(config.newton_cfg.max_iterations, 50);
+        assert_eq!(config.newton_cfg.x_max, Config::default().newton_cfg.x_max);
+    }
+
+    #[test]
+    fn test_invalid_link() {
+        let mut config = Config::default();
+        assert!(apply(&mut config, "type=julia&iter=0").is_err());
+        assert!(apply(&mut config, "type=mandelbrot&min=1,1&max=0,0").is_err());
+        assert!(apply(&mut config, "type=mandelbrot&c=1,1").is_err());
+        assert!(apply(&mut config, "type=mandelbrot&min=1e999,0").is_err());
+        assert!(apply(&mut config, "palette=hsl;0:FF0000").is_err());
+        assert!(apply(&mut config, "type=unknown").is_err());
+        assert_eq!(config.active_config, FractalType::Mandelbrot);
+    }
+
+    #[test]
+    fn test_link_out_of_bounds() {
+        let mut config = Config::default();
+        // the canvas height follows the aspect ratio of the area
+        assert!(apply(&mut config, "type=mandelbrot&min=0,0&max=1e-9,1").is_err());
+        assert!(apply(&mut config, "type=burning_ship&min=0,0&max=1,1e-9").is_err());
+        assert!(apply(&mut config, "type=julia&iter=4000000000").is_err());
+        assert!(apply(&mut config, "type=newton&iter=100001").is_err());
+        assert_eq!(config.mandelbrot_cfg, Config::default().mandelbrot_cfg);
+        assert_eq!(config.active_config, FractalType::Mandelbrot);
+        assert!(apply(&mut config, "type=julia&iter=100000").is_ok());
+    }
+}
@@ -1,140 +1,251 @@
-use seed::{log, prelude::web_sys};
-
-use super::{util::find_escape_radius, MAX_DURATION};
+use super::util::{
+    find_escape_radius, in_main_cardioid_or_bulb, integer_exponent, precise_digits,
+    smooth_iterations, PERIOD_TOLERANCE, SMOOTH_BAILOUT,
+};
 
 use super::{
+    bigfloat::BigComplex,
+    clock::Clock,
     complex::Complex,
-    fractal::{Fractal, Points},
-    Model,
-    stats::Stats
+    fractal::{Counts, Fractal, KnownPoints, Points, Tile},
+    stats::Stats,
+    subdivision::Subdivision,
+    Renderer,
 };
 
+/// Area and iteration settings of a Mandelbrot set image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MandelbrotParams {
+    // the corners are relative to the origin
+    pub origin: BigComplex,
+    pub c_min: Complex,
+    pub c_max: Complex,
+    pub iterations: u32,
+    pub exponent: f64,
+    // skip points of the main cardioid and period-2 bulb, exponent 2 only
+    pub cardioid_check: bool,
+    pub periodicity_check: bool,
+}
+
 pub struct Mandelbrot {
     scale_real: f64,
     scale_imag: f64,
     offset: Complex,
-    x_curr: u32,
-    y_curr: u32,
+    origin: BigComplex,
+    origin_f64: Complex,
+    // decimal places of the arbitrary precision iteration of deep zooms
+    precision: Option<usize>,
     width: u32,
     height: u32,
     iterations: u32,
-    res: Points,
-    done: bool,
+    exponent: f64,
+    int_exponent: Option<u32>,
+    cardioid_check: bool,
+    periodicity_check: bool,
+    // squared distance at which an orbit counts as periodic
+    period_epsilon: f64,
+    known: Option<KnownPoints>,
+    // Mariani-Silver subdivision for the final pass
+    subdivision: bool,
+    clock: Box<dyn Clock>,
 }
 
 impl Mandelbrot {
-    pub fn new(model: &Model) -> Self {
-        log!(format!(
-            "creating fractal with: x_max: {}, x_min: {}",
-            model.config.mandelbrot_cfg.c_max, model.config.mandelbrot_cfg.c_min,
-        ));
-
-        let scale_real = (model.config.mandelbrot_cfg.c_max.real()
-            - model.config.mandelbrot_cfg.c_min.real())
-            / f64::from(model.width);
-        let scale_imag = (model.config.mandelbrot_cfg.c_max.imag()
-            - model.config.mandelbrot_cfg.c_min.imag())
-            / f64::from(model.height);
+    pub fn new(params: &MandelbrotParams, width: u32, height: u32, clock: Box<dyn Clock>) -> Self {
+        let scale_real = (params.c_max.real() - params.c_min.real()) / f64::from(width);
+        let scale_imag = (params.c_max.imag() - params.c_min.imag()) / f64::from(height);
+        let origin_f64 = params.origin.to_complex();
+        let center = Complex::new(
+            origin_f64.real() + f64::midpoint(params.c_min.real(), params.c_max.real()),
+            origin_f64.imag() + f64::midpoint(params.c_min.imag(), params.c_max.imag()),
+        );
+        // non-integer exponents need powf, those stay with f64
+        let int_exponent = integer_exponent(params.exponent);
+        let precision = int_exponent.and_then(|_| {
+            precise_digits(f64::min(scale_real, scale_imag), center)
+        });
 
         Self {
             scale_real,
             scale_imag,
-            offset: model.config.mandelbrot_cfg.c_min,
-            x_curr: 0,
-            y_curr: 0,
-            width: model.width,
-            height: model.height,
-            iterations: model.config.mandelbrot_cfg.max_iterations,
-            res: Points::default(),
-            done: false,
+            offset: params.c_min,
+            origin: params.origin.clone(),
+            origin_f64,
+            precision,
+            width,
+            height,
+            iterations: params.iterations,
+            exponent: params.exponent,
+            int_exponent,
+            cardioid_check: params.cardioid_check,
+            periodicity_check: params.periodicity_check,
+            period_epsilon: (f64::min(scale_real, scale_imag) * PERIOD_TOLERANCE).powi(2),
+            known: None,
+            subdivision: false,
+            clock,
         }
     }
 
-    fn iterate(&self, c: &Complex) -> u32 {
-        let max = find_escape_radius(c.norm()).powi(2);
+    fn iterate(&self, c: &Complex, counts: &mut Counts) -> (u32, f32) {
+        #[allow(clippy::cast_precision_loss)]
+        let inside = (self.iterations + 1, (self.iterations + 1) as f32);
+        if self.cardioid_check && self.int_exponent == Some(2) && in_main_cardioid_or_bulb(c) {
+            counts.short_circuited += 1;
+            return inside;
+        }
+
+        let max = f64::max(find_escape_radius(c.norm(), self.exponent), SMOOTH_BAILOUT).powi(2);
         let mut x = Complex::new(0.0, 0.0);
-        // log!(format!("iterate: start: {}", curr));
-        let mut last: Option<u32> = None;
+        // Brent's cycle detection, the orbit is compared to a point saved after 1, 2, 4, .. steps
+        let mut saved = x;
+        let mut cycle_len = 0u32;
+        let mut cycle_max = 1u32;
         for idx in 1..=self.iterations {
-            x = x * x + *c;
+            x = match self.int_exponent {
+                Some(2) => x * x,
+                Some(exp) => x.powi(exp),
+                None => x.powf(self.exponent),
+            } + *c;
             if x.square_length() >= max {
-                last = Some(idx);
-                break;
+                counts.iterations += idx as usize;
+                return (idx, smooth_iterations(idx, x.square_length(), self.exponent));
+            }
+            if self.periodicity_check {
+                if (x - saved).square_length() < self.period_epsilon {
+                    counts.iterations += idx as usize;
+                    counts.short_circuited += 1;
+                    return inside;
+                }
+                cycle_len += 1;
+                if cycle_len == cycle_max {
+                    saved = x;
+                    cycle_len = 0;
+                    cycle_max = cycle_max.saturating_mul(2);
+                }
             }
         }
 
-        // log!(format!("iterate: end:  {} norm: {} last: {:?}", curr, curr.square_length(), last));
-        last.map_or(self.iterations + 1, |last| last)
+        counts.iterations += self.iterations as usize;
+        inside
     }
-}
 
-impl Fractal for Mandelbrot {
-    fn calculate(&mut self, stats: Option<&mut Stats>) -> &Points {
-        let performance = web_sys::window()
-            .expect("Window not found")
-            .performance()
-            .expect("performance should be available");
-
-        let start = performance.now();
-
-        self.res.x_start = self.x_curr;
-        self.res.y_start = self.y_curr;
-        self.res.num_points = 0;
-
-        let mut x = self.x_curr;
-        let mut y = self.y_curr;
-
-        let mut points_done: Option<usize> = None;
-        let mut last_check = 0usize;
-        let mut iterations = 0usize;
-
-        for count in 0..self.res.values.len() {
-            let calc = Complex::new(
-                f64::from(x).mul_add(self.scale_real,self.offset.real()),
-                f64::from(y).mul_add(self.scale_imag,self.offset.imag()),
-            );
-            let curr = self.iterate(&calc);
-            self.res.values[count] = curr;
-
-            if x < self.width - 1 {
-                x += 1;
+    // the same iteration with arbitrary precision, the escape and period checks only need f64
+    fn iterate_precise(&self, c: &BigComplex, digits: usize, counts: &mut Counts) -> (u32, f32) {
+        #[allow(clippy::cast_precision_loss)]
+        let inside = (self.iterations + 1, (self.iterations + 1) as f32);
+        if self.cardioid_check
+            && self.int_exponent == Some(2)
+            && in_main_cardioid_or_bulb(&c.approx_complex())
+        {
+            counts.short_circuited += 1;
+            return inside;
+        }
+
+        let exponent = self.int_exponent.unwrap_or(2);
+        let max = f64::max(
+            find_escape_radius(c.approx_complex().norm(), self.exponent),
+            SMOOTH_BAILOUT,
+        )
+        .powi(2);
+        let mut x = BigComplex::default();
+        let mut saved = x.clone();
+        let mut cycle_len = 0u32;
+        let mut cycle_max = 1u32;
+        for idx in 1..=self.iterations {
+            x = &if exponent == 2 {
+                x.square(digits)
             } else {
-                x = 0;
-                y += 1;
-                if y >= self.height {
-                    self.done = true;
-                    points_done = Some(count + 1);
-                    break;
-                }
+                x.powi(exponent, digits)
+            } + c;
+            let square_length = x.approx_complex().square_length();
+            if square_length >= max {
+                counts.iterations += idx as usize;
+                return (idx, smooth_iterations(idx, square_length, self.exponent));
             }
-
-            iterations += curr as usize;
-            if iterations - last_check > 100 {
-                last_check = iterations;
-                if performance.now() - start >= MAX_DURATION {
-                    points_done = Some(count + 1);
-                    break;
+            if self.periodicity_check {
+                if (&x - &saved).approx_complex().square_length() < self.period_epsilon {
+                    counts.iterations += idx as usize;
+                    counts.short_circuited += 1;
+                    return inside;
+                }
+                cycle_len += 1;
+                if cycle_len == cycle_max {
+                    saved = x.clone();
+                    cycle_len = 0;
+                    cycle_max = cycle_max.saturating_mul(2);
                 }
             }
         }
 
-        if let Some(points) = points_done {
-            self.res.num_points = points;
+        counts.iterations += self.iterations as usize;
+        inside
+    }
+
+    // deep zooms are beyond f64, their pixels are placed and iterated with arbitrary precision
+    fn iterate_at(&self, x: u32, y: u32, counts: &mut Counts) -> (u32, f32) {
+        let c = Complex::new(
+            f64::from(x).mul_add(self.scale_real, self.offset.real()),
+            f64::from(y).mul_add(self.scale_imag, self.offset.imag()),
+        );
+        if let Some(digits) = self.precision {
+            let c = (&self.origin + &BigComplex::from(c)).truncate(digits);
+            self.iterate_precise(&c, digits, counts)
         } else {
-            self.res.num_points = self.res.values.len();
+            self.iterate(&(c + self.origin_f64), counts)
         }
+    }
+}
 
-        self.x_curr = x;
-        self.y_curr = y;
+impl Fractal for Mandelbrot {
+    fn calculate(&mut self, tile: &Tile, stats: Option<&mut Stats>) -> Points {
+        let start = self.clock.now();
+        let mut counts = Counts::default();
+
+        if self.subdivision && tile.block == 1 {
+            let (points, filled) = Subdivision::calculate(tile, self.iterations + 1, |x, y| {
+                if let Some((value, smooth, _)) =
+                    self.known.as_ref().and_then(|known| known.get(x, y))
+                {
+                    (value, smooth)
+                } else {
+                    self.iterate_at(x, y, &mut counts)
+                }
+            });
+            if let Some(stats) = stats {
+                stats.update(counts.iterations, points.values.len(), start, self.clock.now());
+                stats.add_filled(filled);
+                stats.add_short_circuited(counts.short_circuited);
+            }
+            return points;
+        }
+
+        let mut points = Points::new(*tile);
+        for idx in 0..tile.num_points() {
+            let (x, y) = tile.position(idx);
+            let (value, smooth) = if let Some((value, smooth, _)) =
+                self.known.as_ref().and_then(|known| known.get(x, y))
+            {
+                (value, smooth)
+            } else {
+                self.iterate_at(x, y, &mut counts)
+            };
+            points.values[idx] = value;
+            points.smooth[idx] = smooth;
+        }
 
         if let Some(stats) = stats {
-            stats.update(iterations, self.res.num_points, start);
+            stats.update(counts.iterations, points.values.len(), start, self.clock.now());
+            stats.add_short_circuited(counts.short_circuited);
         }
 
-        &self.res
+        points
+    }
+
+    fn set_known_points(&mut self, known: KnownPoints) {
+        self.known = Some(known);
     }
 
-    fn is_done(&self) -> bool {
-        self.done
+    fn set_renderer(&mut self, renderer: Renderer) {
+        self.subdivision = renderer == Renderer::Subdivision;
     }
 }
@@ -0,0 +1,299 @@
+use seed::browser::web_storage::WebStorageError;
+use seed::prelude::{LocalStorage, WebStorage};
+use serde::Serialize;
+use std::convert::TryFrom;
+use serde_json::{Map, Value};
+
+use super::{
+    bigfloat::BigComplex, palette::Palette, BurningShipCfg, Config, NewtonCfg, Renderer,
+    JULIA_DEFAULT_EXPONENT, MANDELBROT_DEFAULT_EXPONENT, STORAGE_KEY, ZOOM_DEFAULT_FACTOR,
+};
+
+/// Version of the stored `Config` layout, increment it and add a migration when changing `Config`.
+pub const CONFIG_VERSION: u32 = 6;
+
+// configs before version 2 were stored without version under this key
+const LEGACY_STORAGE_KEY: &str = "seed_fractals_v1";
+// a config that could not be migrated is kept here instead of being overwritten
+const BACKUP_STORAGE_KEY: &str = "seed_fractals_config_backup";
+
+/// Load the stored config, migrating it from older versions.
+/// Returns `Ok(None)` if nothing was stored.
+pub fn load_config() -> Result<Option<Config>, String> {
+    let (key, value) = if let Ok(value) = LocalStorage::get::<Value>(STORAGE_KEY) {
+        (STORAGE_KEY, value)
+    } else if let Ok(value) = LocalStorage::get::<Value>(LEGACY_STORAGE_KEY) {
+        (LEGACY_STORAGE_KEY, value)
+    } else {
+        return Ok(None);
+    };
+
+    match migrate(value.clone()) {
+        Ok(config) => {
+            let outdated = stored_version(&value) != Ok(CONFIG_VERSION);
+            if key != STORAGE_KEY || outdated {
+                LocalStorage::insert(STORAGE_KEY, &config)
+                    .map_err(|err| format!("failed to save migrated config: {err:?}"))?;
+                if key != STORAGE_KEY {
+                    // removing is not essential, the new key is read first
+                    let _ = LocalStorage::remove(key);
+                }
+            }
+            Ok(Some(config))
+        }
+        Err(err) => {
+            let _ = LocalStorage::insert(BACKUP_STORAGE_KEY, &value);
+            Err(err)
+        }
+    }
+}
+
+/// Load the value stored under `key` with `read`, returns `Ok(None)` if nothing was stored.
+/// A value that cannot be read is kept under `backup_key` instead of being overwritten.
+pub fn load_stored<T>(
+    key: &str,
+    backup_key: &str,
+    read: impl FnOnce(Value) -> Result<T, String>,
+) -> Result<Option<T>, String> {
+    let value = match LocalStorage::get::<Value>(key) {
+        Ok(value) => value,
+        Err(WebStorageError::KeyNotFoundError) => return Ok(None),
+        Err(err) => return Err(format!("failed to read {key}: {err:?}")),
+    };
+    read(value.clone()).map(Some).inspect_err(|_| {
+        let _ = LocalStorage::insert(backup_key, &value);
+    })
+}
+
+/// Version of a stored layout, values saved before it was versioned are version 1.
+pub fn stored_version(value: &Value) -> Result<u32, String> {
+    value.get("version").map_or(Ok(1), |version| {
+        version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| format!("invalid config version: {version}"))
+    })
+}
+
+/// Bring a stored config of any known version up to date.
+pub fn migrate(mut value: Value) -> Result<Config, String> {
+    let version = stored_version(&value)?;
+    if version > CONFIG_VERSION {
+        return Err(format!(
+            "config version {version} is newer than the supported version {CONFIG_VERSION}"
+        ));
+    }
+
+    if version < 2 {
+        migrate_v1(&mut value)?;
+    }
+    if version < 3 {
+        migrate_v2(&mut value)?;
+    }
+    if version < 4 {
+        migrate_v3(&mut value)?;
+    }
+    if version < 5 {
+        migrate_v4(&mut value)?;
+    }
+    if version < 6 {
+        migrate_v5(&mut value)?;
+    }
+
+    serde_json::from_value(value).map_err(|err| format!("invalid config: {err}"))
+}
+
+fn insert_missing<T: Serialize>(
+    object: &mut Map<String, Value>,
+    key: &str,
+    default: T,
+) -> Result<(), String> {
+    if !object.contains_key(key) {
+        let default = serde_json::to_value(default)
+            .map_err(|err| format!("failed to serialize default for {key}: {err}"))?;
+        object.insert(key.to_string(), default);
+    }
+    Ok(())
+}
+
+fn object_mut<'a>(
+    object: &'a mut Map<String, Value>,
+    key: &str,
+) -> Result<&'a mut Map<String, Value>, String> {
+    object
+        .get_mut(key)
+        .and_then(Value::as_object_mut)
+        .ok_or_else(|| format!("{key} is missing or not an object"))
+}
+
+// Version 1 only knew the Mandelbrot and Julia sets with a fixed exponent and palette.
+// Unversioned configs of later development builds carry some of the newer fields, those are kept.
+fn migrate_v1(value: &mut Value) -> Result<(), String> {
+    let config = value.as_object_mut().ok_or("config is not an object")?;
+
+    insert_missing(
+        object_mut(config, "julia_set_cfg")?,
+        "exponent",
+        JULIA_DEFAULT_EXPONENT,
+    )?;
+    insert_missing(
+        object_mut(config, "mandelbrot_cfg")?,
+        "exponent",
+        MANDELBROT_DEFAULT_EXPONENT,
+    )?;
+    insert_missing(config, "burning_ship_cfg", BurningShipCfg::default())?;
+    insert_missing(config, "newton_cfg", NewtonCfg::default())?;
+    insert_missing(config, "palette", Palette::default())?;
+    insert_missing(config, "smooth_coloring", true)?;
+    insert_missing(config, "zoom_factor", ZOOM_DEFAULT_FACTOR)?;
+    config.insert("version".to_string(), Value::from(2));
+    Ok(())
+}
+
+// Version 3 added progressive rendering.
+fn migrate_v2(value: &mut Value) -> Result<(), String> {
+    let config = value.as_object_mut().ok_or("config is not an object")?;
+    insert_missing(config, "progressive", true)?;
+    config.insert("version".to_string(), Value::from(3));
+    Ok(())
+}
+
+// Version 4 added the choice of renderer.
+fn migrate_v3(value: &mut Value) -> Result<(), String> {
+    let config = value.as_object_mut().ok_or("config is not an object")?;
+    insert_missing(config, "renderer", Renderer::Scanline)?;
+    config.insert("version".to_string(), Value::from(4));
+    Ok(())
+}
+
+// Version 5 added the interior checks.
+fn migrate_v4(value: &mut Value) -> Result<(), String> {
+    let config = value.as_object_mut().ok_or("config is not an object")?;
+    insert_missing(config, "cardioid_check", true)?;
+    insert_missing(config, "periodicity_check", true)?;
+    config.insert("version".to_string(), Value::from(5));
+    Ok(())
+}
+
+// Version 6 added the arbitrary precision origin of deep zooms.
+fn migrate_v5(value: &mut Value) -> Result<(), String> {
+    let config = value.as_object_mut().ok_or("config is not an object")?;
+    insert_missing(
+        object_mut(config, "julia_set_cfg")?,
+        "origin",
+        BigComplex::default(),
+    )?;
+    insert_missing(
+        object_mut(config, "mandelbrot_cfg")?,
+        "origin",
+        BigComplex::default(),
+    )?;
+    config.insert("version".to_string(), Value::from(6));
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{migrate, CONFIG_VERSION};
+    use crate::{
+        bigfloat::BigComplex,
+        complex::Complex,
+        palette::{Interpolation, Palette},
+        Config, FractalType, NewtonCfg, Renderer, ZOOM_DEFAULT_FACTOR,
+    };
+    use serde_json::Value;
+
+    fn fixture(json: &str) -> Value {
+        serde_json::from_str(json).expect("invalid fixture")
+    }
+
+    #[test]
+    fn test_migrate_v1() {
+        let config = migrate(fixture(include_str!("fixtures/config_v1.json")))
+            .expect("failed to migrate v1 config");
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert!(config.view_stats);
+        assert_eq!(config.active_config, FractalType::JuliaSet);
+        assert_eq!(config.julia_set_cfg.max_iterations, 600);
+        assert_eq!(config.julia_set_cfg.c, Complex::new(-0.7, 0.27015));
+        assert!((config.julia_set_cfg.exponent - 2.0).abs() < f64::EPSILON);
+        assert_eq!(config.mandelbrot_cfg.c_min, Complex::new(-0.8, 0.2));
+        assert_eq!(config.mandelbrot_cfg.origin, BigComplex::default());
+        assert_eq!(config.newton_cfg, NewtonCfg::default());
+        assert_eq!(config.palette, Palette::default());
+        assert!(config.smooth_coloring);
+        assert!(config.progressive);
+        assert_eq!(config.renderer, Renderer::Scanline);
+        assert!(config.cardioid_check && config.periodicity_check);
+        assert!((config.zoom_factor - ZOOM_DEFAULT_FACTOR).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_migrate_v2() {
+        let config = migrate(fixture(include_str!("fixtures/config_v2.json")))
+            .expect("failed to load v2 config");
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.active_config, FractalType::Newton);
+        assert!(!config.smooth_coloring);
+        assert!(config.progressive);
+        assert!((config.julia_set_cfg.exponent - 3.0).abs() < f64::EPSILON);
+        assert_eq!(config.newton_cfg.coefficients, vec![1.0, 0.0, 0.0, 0.0, -1.0]);
+        assert_eq!(config.palette.interpolation, Interpolation::Rgb);
+        assert_eq!(config.palette.stops.len(), 3);
+    }
+
+    #[test]
+    fn test_migrate_v3() {
+        let config = migrate(fixture(include_str!("fixtures/config_v3.json")))
+            .expect("failed to migrate v3 config");
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.active_config, FractalType::BurningShip);
+        assert!(!config.progressive);
+        assert_eq!(config.burning_ship_cfg.max_iterations, 250);
+        assert_eq!(config.renderer, Renderer::Scanline);
+        assert!(config.cardioid_check && config.periodicity_check);
+        assert_eq!(config.julia_set_cfg.origin, BigComplex::default());
+    }
+
+    #[test]
+    fn test_migrate_v4() {
+        let config = migrate(fixture(include_str!("fixtures/config_v4.json")))
+            .expect("failed to migrate v4 config");
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.active_config, FractalType::Mandelbrot);
+        assert_eq!(config.renderer, Renderer::Subdivision);
+        assert_eq!(config.mandelbrot_cfg.c_min, Complex::new(-0.76, 0.11));
+        assert!(config.cardioid_check && config.periodicity_check);
+        assert_eq!(config.mandelbrot_cfg.origin, BigComplex::default());
+    }
+
+    #[test]
+    fn test_migrate_v5() {
+        let config = migrate(fixture(include_str!("fixtures/config_v5.json")))
+            .expect("failed to migrate v5 config");
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert!(!config.cardioid_check && !config.periodicity_check);
+        assert!((config.zoom_factor - 4.0).abs() < f64::EPSILON);
+        assert_eq!(config.mandelbrot_cfg.max_iterations, 800);
+        assert_eq!(config.mandelbrot_cfg.origin, BigComplex::default());
+        assert_eq!(config.julia_set_cfg.origin, BigComplex::default());
+    }
+
+    #[test]
+    fn test_current_version() {
+        let value = serde_json::to_value(Config::default()).expect("failed to serialize config");
+        assert_eq!(value["version"], Value::from(CONFIG_VERSION));
+        assert!(migrate(value).is_ok());
+    }
+
+    #[test]
+    fn test_migrate_invalid() {
+        let mut value = fixture(include_str!("fixtures/config_v2.json"));
+        value["version"] = Value::from(CONFIG_VERSION + 1);
+        assert!(migrate(value).is_err());
+
+        let mut value = fixture(include_str!("fixtures/config_v1.json"));
+        value["julia_set_cfg"] = Value::Null;
+        assert!(migrate(value).is_err());
+    }
+}
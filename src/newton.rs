@@ -0,0 +1,118 @@
+use super::{
+    clock::Clock,
+    complex::Complex,
+    fractal::{Fractal, KnownPoints, Points, Tile, NO_ROOT},
+    polynomial::Polynomial,
+    stats::Stats,
+    Renderer,
+};
+
+// squared distance to a root that counts as converged
+const ROOT_EPSILON: f64 = 1e-10;
+
+/// Area, polynomial and iteration settings of a Newton fractal image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewtonParams {
+    pub x_min: Complex,
+    pub x_max: Complex,
+    // highest power first
+    pub coefficients: Vec<f64>,
+    pub iterations: u32,
+}
+
+pub struct Newton {
+    scale_real: f64,
+    scale_imag: f64,
+    offset: Complex,
+    polynomial: Polynomial,
+    roots: Vec<Complex>,
+    width: u32,
+    height: u32,
+    iterations: u32,
+    known: Option<KnownPoints>,
+    clock: Box<dyn Clock>,
+}
+
+impl Newton {
+    pub fn new(params: &NewtonParams, width: u32, height: u32, clock: Box<dyn Clock>) -> Self {
+        let scale_real = (params.x_max.real() - params.x_min.real()) / f64::from(width);
+        let scale_imag = (params.x_max.imag() - params.x_min.imag()) / f64::from(height);
+
+        let polynomial = Polynomial::new(&params.coefficients);
+        let roots = polynomial.roots();
+
+        Self {
+            scale_real,
+            scale_imag,
+            offset: params.x_min,
+            polynomial,
+            roots,
+            width,
+            height,
+            iterations: params.iterations,
+            known: None,
+            clock,
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn iterate(&self, x: &Complex) -> (u32, u8) {
+        let mut curr = *x;
+        for idx in 1..=self.iterations {
+            let (value, derivative) = self.polynomial.eval(curr);
+            if derivative.square_length() == 0.0 {
+                break;
+            }
+            curr -= value / derivative;
+
+            if let Some(root) = self
+                .roots
+                .iter()
+                .position(|root| (curr - *root).square_length() < ROOT_EPSILON)
+            {
+                return (idx, root as u8);
+            }
+        }
+
+        (self.iterations + 1, NO_ROOT)
+    }
+}
+
+impl Fractal for Newton {
+    fn calculate(&mut self, tile: &Tile, stats: Option<&mut Stats>) -> Points {
+        let start = self.clock.now();
+        let mut iterations = 0usize;
+
+        let mut points = Points::new(*tile);
+        for idx in 0..tile.num_points() {
+            let (x, y) = tile.position(idx);
+            let (value, root) = if let Some((value, _, root)) =
+                self.known.as_ref().and_then(|known| known.get(x, y))
+            {
+                (value, root)
+            } else {
+                let res = self.iterate(&Complex::new(
+                    f64::from(x).mul_add(self.scale_real, self.offset.real()),
+                    f64::from(y).mul_add(self.scale_imag, self.offset.imag()),
+                ));
+                iterations += res.0 as usize;
+                res
+            };
+            points.values[idx] = value;
+            points.roots[idx] = root;
+        }
+
+        if let Some(stats) = stats {
+            stats.update(iterations, points.values.len(), start, self.clock.now());
+        }
+
+        points
+    }
+
+    fn set_known_points(&mut self, known: KnownPoints) {
+        self.known = Some(known);
+    }
+
+    // basins of attraction can enclose other basins, a uniform border says nothing about the interior
+    fn set_renderer(&mut self, _renderer: Renderer) {}
+}
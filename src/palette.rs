@@ -0,0 +1,296 @@
+use serde::{Deserialize, Serialize};
+
+pub const MIN_STOPS: usize = 2;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Interpolation {
+    Rgb,
+    Hsl,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColorStop {
+    pub position: f32,
+    pub color: [u8; 3],
+}
+
+/// A color gradient defined by color stops at positions in 0.0..=1.0.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Palette {
+    pub interpolation: Interpolation,
+    pub stops: Vec<ColorStop>,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        // the original fixed palette, hue 0..300 with full saturation
+        Self {
+            interpolation: Interpolation::Hsl,
+            stops: vec![
+                ColorStop {
+                    position: 0.0,
+                    color: [0xFF, 0x00, 0x00],
+                },
+                ColorStop {
+                    position: 1.0,
+                    color: [0xFF, 0x00, 0xFF],
+                },
+            ],
+        }
+    }
+}
+
+impl Palette {
+    pub fn sort(&mut self) {
+        self.stops
+            .sort_by(|a, b| a.position.partial_cmp(&b.position).unwrap_or(std::cmp::Ordering::Equal));
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.stops.len() >= MIN_STOPS
+            && self
+                .stops
+                .iter()
+                .all(|stop| (0.0..=1.0).contains(&stop.position))
+    }
+
+    /// Insert a stop in the middle of the widest gap between two stops.
+    pub fn add_stop(&mut self) {
+        if let Some(idx) = (1..self.stops.len()).max_by(|a, b| {
+            let gap_a = self.stops[*a].position - self.stops[*a - 1].position;
+            let gap_b = self.stops[*b].position - self.stops[*b - 1].position;
+            gap_a.partial_cmp(&gap_b).unwrap_or(std::cmp::Ordering::Equal)
+        }) {
+            let position = f32::midpoint(self.stops[idx].position, self.stops[idx - 1].position);
+            let [r, g, b, _] = self.color_at(position);
+            self.stops.insert(
+                idx,
+                ColorStop {
+                    position,
+                    color: [r, g, b],
+                },
+            );
+        }
+    }
+
+    pub fn remove_stop(&mut self, idx: usize) {
+        if self.stops.len() > MIN_STOPS && idx < self.stops.len() {
+            self.stops.remove(idx);
+        }
+    }
+
+    /// Color for a position in 0.0..=1.0, stops are expected to be sorted.
+    pub fn color_at(&self, position: f32) -> [u8; 4] {
+        let first = self.stops.first().expect("palette without color stops");
+        let last = self.stops.last().expect("palette without color stops");
+        if position <= first.position {
+            return rgb_to_rgba(first.color);
+        }
+        if position >= last.position {
+            return rgb_to_rgba(last.color);
+        }
+
+        let idx = self
+            .stops
+            .iter()
+            .position(|stop| stop.position > position)
+            .unwrap_or(self.stops.len() - 1);
+        let (from, to) = (&self.stops[idx - 1], &self.stops[idx]);
+        let ratio = (position - from.position) / (to.position - from.position);
+
+        match self.interpolation {
+            Interpolation::Rgb => {
+                let lerp = |a: u8, b: u8| -> u8 {
+                    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                    let res = (f32::from(b) - f32::from(a)).mul_add(ratio, f32::from(a)).round() as u8;
+                    res
+                };
+                [
+                    lerp(from.color[0], to.color[0]),
+                    lerp(from.color[1], to.color[1]),
+                    lerp(from.color[2], to.color[2]),
+                    0xFF,
+                ]
+            }
+            Interpolation::Hsl => {
+                let (h_from, s_from, l_from) = rgb_to_hsl(from.color);
+                let (h_to, s_to, l_to) = rgb_to_hsl(to.color);
+                hsl_to_rgb(
+                    (h_to - h_from).mul_add(ratio, h_from),
+                    (s_to - s_from).mul_add(ratio, s_from),
+                    (l_to - l_from).mul_add(ratio, l_from),
+                )
+            }
+        }
+    }
+
+    /// Precalculated colors for iteration counts 0..=steps.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn build_table(&self, steps: u32) -> Vec<[u8; 4]> {
+        (0..=steps)
+            .map(|step| self.color_at(step as f32 / steps as f32))
+            .collect()
+    }
+}
+
+const fn rgb_to_rgba(color: [u8; 3]) -> [u8; 4] {
+    [color[0], color[1], color[2], 0xFF]
+}
+
+#[allow(clippy::many_single_char_names, clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> [u8; 4] {
+    // see: https://www.rapidtables.com/convert/color/hsl-to-rgb.html
+
+    assert!((0.0..=1.0).contains(&saturation));
+    assert!((0.0..=1.0).contains(&lightness));
+
+    let safe_hue = if hue >= 360.0 {
+        hue % 360.0
+    } else {
+        hue
+    };
+
+    let c = (1.0 - f32::abs(2.0f32.mul_add(lightness, -1.0))) * saturation;
+    let x = c * (1.0 - ((safe_hue / 60.0) % 2.0 - 1.0).abs());
+    let m = lightness - c / 2.0;
+    let (r, g, b) = match safe_hue as u32 {
+        0..=59 => (c, x, 0.0),
+        60..=119 => (x, c, 0.0),
+        120..=179 => (0.0, c, x),
+        180..=239 => (0.0, x, c),
+        240..=299 => (x, 0.0, c),
+        300..=359 => (c, 0.0, x),
+        _ => {
+            panic!("invalid hue value");
+        }
+    };
+
+    [
+        (f32::floor((r + m) * 255.0).abs() as u32 % 0x100) as u8,
+        (f32::floor((g + m) * 255.0).abs() as u32 % 0x100) as u8,
+        (f32::floor((b + m) * 255.0).abs() as u32 % 0x100) as u8,
+        0xFF,
+    ]
+}
+
+#[allow(clippy::many_single_char_names)]
+pub fn rgb_to_hsl(color: [u8; 3]) -> (f32, f32, f32) {
+    // see: https://www.rapidtables.com/convert/color/rgb-to-hsl.html
+    let r = f32::from(color[0]) / 255.0;
+    let g = f32::from(color[1]) / 255.0;
+    let b = f32::from(color[2]) / 255.0;
+
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+
+    let lightness = f32::midpoint(max, min);
+    if delta <= f32::EPSILON {
+        return (0.0, 0.0, lightness);
+    }
+
+    let saturation = (delta / (1.0 - (2.0f32.mul_add(lightness, -1.0)).abs())).min(1.0);
+    let hue = if (max - r).abs() <= f32::EPSILON {
+        60.0 * (((g - b) / delta) % 6.0)
+    } else if (max - g).abs() <= f32::EPSILON {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+
+    (if hue < 0.0 { hue + 360.0 } else { hue }, saturation, lightness)
+}
+
+// parses colors in the #RRGGBB format, anything else is treated as black
+pub fn html_to_rgba(color: &str) -> [u8; 4] {
+    let parse = |idx: usize| {
+        color
+            .get(idx..idx + 2)
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            .unwrap_or(0)
+    };
+    if color.len() == 7 && color.starts_with('#') {
+        [parse(1), parse(3), parse(5), 0xFF]
+    } else {
+        [0, 0, 0, 0xFF]
+    }
+}
+
+pub fn rgb_to_html(color: [u8; 3]) -> String {
+    format!("#{:0>2X}{:0>2X}{:0>2X}", color[0], color[1], color[2])
+}
+
+#[cfg(test)]
+mod test {
+    use super::{hsl_to_rgb, html_to_rgba, rgb_to_hsl, rgb_to_html, ColorStop, Interpolation, Palette};
+
+    #[test]
+    fn test_hsl_to_rgb() {
+        assert_eq!(hsl_to_rgb(0.0, 1.0, 0.5), [0xFF, 0x00, 0x00, 0xFF]);
+        assert_eq!(hsl_to_rgb(60.0, 1.0, 0.5), [0xFF, 0xFF, 0x00, 0xFF]);
+        assert_eq!(hsl_to_rgb(120.0, 1.0, 0.5), [0x00, 0xFF, 0x00, 0xFF]);
+        assert_eq!(hsl_to_rgb(180.0, 1.0, 0.5), [0x00, 0xFF, 0xFF, 0xFF]);
+        assert_eq!(hsl_to_rgb(240.0, 1.0, 0.5), [0x00, 0x00, 0xFF, 0xFF]);
+        assert_eq!(hsl_to_rgb(300.0, 1.0, 0.5), [0xFF, 0x00, 0xFF, 0xFF]);
+        assert_eq!(hsl_to_rgb(360.0, 1.0, 0.5), [0xFF, 0x00, 0x00, 0xFF]);
+        assert_eq!(hsl_to_rgb(340.0, 1.0, 0.5), [0xFF, 0x00, 0x55, 0xFF]);
+        assert_eq!(hsl_to_rgb(120.0, 1.0, 0.25), [0x00, 0x7F, 0x00, 0xFF]);
+        assert_eq!(hsl_to_rgb(240.0, 0.0, 0.5), [0x7F, 0x7F, 0x7F, 0xFF]);
+        assert_eq!(hsl_to_rgb(480.0, 1.0, 0.5), [0x00, 0xFF, 0x00, 0xFF]);
+    }
+
+    #[test]
+    fn test_rgb_to_hsl() {
+        assert_eq!(rgb_to_hsl([0xFF, 0x00, 0x00]), (0.0, 1.0, 0.5));
+        assert_eq!(rgb_to_hsl([0xFF, 0x00, 0xFF]), (300.0, 1.0, 0.5));
+        assert_eq!(rgb_to_hsl([0x00, 0x00, 0x00]), (0.0, 0.0, 0.0));
+        let (hue, saturation, lightness) = rgb_to_hsl([0x00, 0x80, 0x80]);
+        assert!((hue - 180.0).abs() < 0.01);
+        assert!((saturation - 1.0).abs() < 0.01);
+        assert!((lightness - 0.25).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_html_colors() {
+        assert_eq!(html_to_rgba("#000000"), [0x00, 0x00, 0x00, 0xFF]);
+        assert_eq!(html_to_rgba("#12AbFF"), [0x12, 0xAB, 0xFF, 0xFF]);
+        assert_eq!(html_to_rgba("red"), [0x00, 0x00, 0x00, 0xFF]);
+        assert_eq!(rgb_to_html([0x12, 0xAB, 0x0F]), "#12AB0F");
+    }
+
+    #[test]
+    fn test_default_palette() {
+        // matches the former hue based coloring
+        let palette = Palette::default();
+        assert_eq!(palette.color_at(0.0), [0xFF, 0x00, 0x00, 0xFF]);
+        assert_eq!(palette.color_at(0.2), hsl_to_rgb(60.0, 1.0, 0.5));
+        assert_eq!(palette.color_at(0.6), hsl_to_rgb(180.0, 1.0, 0.5));
+        assert_eq!(palette.color_at(1.0), [0xFF, 0x00, 0xFF, 0xFF]);
+    }
+
+    #[test]
+    fn test_rgb_interpolation() {
+        let mut palette = Palette {
+            interpolation: Interpolation::Rgb,
+            stops: vec![
+                ColorStop { position: 1.0, color: [0xFF, 0xFF, 0xFF] },
+                ColorStop { position: 0.0, color: [0x00, 0x00, 0x00] },
+                ColorStop { position: 0.5, color: [0xFF, 0x00, 0x00] },
+            ],
+        };
+        palette.sort();
+        assert!(palette.is_valid());
+        assert_eq!(palette.color_at(0.25), [0x80, 0x00, 0x00, 0xFF]);
+        assert_eq!(palette.color_at(0.75), [0xFF, 0x80, 0x80, 0xFF]);
+        assert_eq!(palette.build_table(4)[2], [0xFF, 0x00, 0x00, 0xFF]);
+
+        palette.add_stop();
+        assert_eq!(palette.stops.len(), 4);
+        assert!((palette.stops[2].position - 0.75).abs() < f32::EPSILON);
+
+        palette.remove_stop(1);
+        palette.remove_stop(1);
+        palette.remove_stop(1);
+        assert_eq!(palette.stops.len(), 2);
+    }
+}
@@ -0,0 +1,129 @@
+use std::convert::TryFrom;
+
+use super::complex::Complex;
+
+const MAX_ROOT_ITERATIONS: u32 = 500;
+const ROOT_TOLERANCE: f64 = 1e-24;
+
+/// A polynomial with complex coefficients, highest degree first.
+pub struct Polynomial {
+    coefficients: Vec<Complex>,
+}
+
+impl Polynomial {
+    pub fn new(coefficients: &[f64]) -> Self {
+        // leading zeros do not contribute to the degree
+        let coefficients = coefficients
+            .iter()
+            .skip_while(|coeff| **coeff == 0.0)
+            .map(|coeff| Complex::new(*coeff, 0.0))
+            .collect();
+        Self { coefficients }
+    }
+
+    pub const fn degree(&self) -> usize {
+        self.coefficients.len().saturating_sub(1)
+    }
+
+    /// Evaluate the polynomial and its derivative at x using Horner's scheme.
+    pub fn eval(&self, x: Complex) -> (Complex, Complex) {
+        let mut value = Complex::new(0.0, 0.0);
+        let mut derivative = Complex::new(0.0, 0.0);
+        for coeff in &self.coefficients {
+            derivative = derivative * x + value;
+            value = value * x + *coeff;
+        }
+        (value, derivative)
+    }
+
+    /// Find all roots using the Durand-Kerner method.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn roots(&self) -> Vec<Complex> {
+        if self.degree() == 0 {
+            return Vec::new();
+        }
+
+        let lead = self.coefficients[0];
+        let monic = Self {
+            coefficients: self.coefficients.iter().map(|coeff| *coeff / lead).collect(),
+        };
+
+        // the usual starting values, powers of a number that is neither real nor a root of unity
+        let seed = Complex::new(0.4, 0.9);
+        let mut roots: Vec<Complex> = (0..self.degree())
+            .map(|idx| seed.powi(u32::try_from(idx).expect("degree fits into u32")))
+            .collect();
+
+        for _ in 0..MAX_ROOT_ITERATIONS {
+            let mut max_delta = 0.0f64;
+            for idx in 0..roots.len() {
+                let mut denom = Complex::new(1.0, 0.0);
+                for (other_idx, other) in roots.iter().enumerate() {
+                    if other_idx != idx {
+                        denom *= roots[idx] - *other;
+                    }
+                }
+                let delta = monic.eval(roots[idx]).0 / denom;
+                roots[idx] -= delta;
+                max_delta = max_delta.max(delta.square_length());
+            }
+            if max_delta < ROOT_TOLERANCE {
+                break;
+            }
+        }
+        roots
+    }
+}
+
+#[must_use]
+pub fn parse_coefficients(text: &str) -> Option<Vec<f64>> {
+    text.split(',')
+        .map(|coeff| coeff.trim().parse::<f64>().ok())
+        .collect()
+}
+
+#[must_use]
+pub fn format_coefficients(coefficients: &[f64]) -> String {
+    coefficients
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::{format_coefficients, parse_coefficients, Polynomial};
+    use crate::complex::Complex;
+
+    #[test]
+    fn test_eval() {
+        // x^3 - 2x + 1
+        let poly = Polynomial::new(&[0.0, 1.0, 0.0, -2.0, 1.0]);
+        assert_eq!(poly.degree(), 3);
+        let (value, derivative) = poly.eval(Complex::new(2.0, 0.0));
+        assert!((value.real() - 5.0).abs() < 1e-12);
+        assert!((derivative.real() - 10.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_roots() {
+        // x^3 - 1
+        let poly = Polynomial::new(&[1.0, 0.0, 0.0, -1.0]);
+        let roots = poly.roots();
+        assert_eq!(roots.len(), 3);
+        for root in &roots {
+            assert!(poly.eval(*root).0.norm() < 1e-9);
+        }
+        assert!(roots
+            .iter()
+            .any(|root| (*root - Complex::new(1.0, 0.0)).norm() < 1e-9));
+    }
+
+    #[test]
+    fn test_parse_coefficients() {
+        assert_eq!(parse_coefficients("1, 0,-1"), Some(vec![1.0, 0.0, -1.0]));
+        assert_eq!(parse_coefficients("1, x"), None);
+        assert_eq!(format_coefficients(&[1.0, 0.0, -1.5]), "1, 0, -1.5");
+    }
+}
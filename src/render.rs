@@ -0,0 +1,293 @@
+use super::{
+    burning_ship::{BurningShip, BurningShipParams},
+    clock::Clock,
+    fractal::{Fractal, IterationBuffer},
+    julia_set::{JuliaSet, JuliaSetParams},
+    mandelbrot::{Mandelbrot, MandelbrotParams},
+    newton::{Newton, NewtonParams},
+    scheduler::{Order, Scheduler, TILE_SIZE},
+    stats::Stats,
+    Config, FractalType, Renderer,
+};
+
+impl From<&Config> for MandelbrotParams {
+    fn from(config: &Config) -> Self {
+        Self {
+            origin: config.mandelbrot_cfg.origin.clone(),
+            c_min: config.mandelbrot_cfg.c_min,
+            c_max: config.mandelbrot_cfg.c_max,
+            iterations: config.mandelbrot_cfg.max_iterations,
+            exponent: config.mandelbrot_cfg.exponent,
+            cardioid_check: config.cardioid_check,
+            periodicity_check: config.periodicity_check,
+        }
+    }
+}
+
+impl From<&Config> for JuliaSetParams {
+    fn from(config: &Config) -> Self {
+        Self {
+            origin: config.julia_set_cfg.origin.clone(),
+            x_min: config.julia_set_cfg.x_min,
+            x_max: config.julia_set_cfg.x_max,
+            c: config.julia_set_cfg.c,
+            iterations: config.julia_set_cfg.max_iterations,
+            exponent: config.julia_set_cfg.exponent,
+            periodicity_check: config.periodicity_check,
+        }
+    }
+}
+
+impl From<&Config> for BurningShipParams {
+    fn from(config: &Config) -> Self {
+        Self {
+            c_min: config.burning_ship_cfg.c_min,
+            c_max: config.burning_ship_cfg.c_max,
+            iterations: config.burning_ship_cfg.max_iterations,
+        }
+    }
+}
+
+impl From<&Config> for NewtonParams {
+    fn from(config: &Config) -> Self {
+        Self {
+            x_min: config.newton_cfg.x_min,
+            x_max: config.newton_cfg.x_max,
+            coefficients: config.newton_cfg.coefficients.clone(),
+            iterations: config.newton_cfg.max_iterations,
+        }
+    }
+}
+
+/// Renderer used for the active fractal of `config`, Newton fractals and the Burning Ship only
+/// support scanline.
+pub const fn renderer(config: &Config) -> Renderer {
+    if matches!(
+        config.active_config,
+        FractalType::Newton | FractalType::BurningShip
+    ) {
+        Renderer::Scanline
+    } else {
+        config.renderer
+    }
+}
+
+/// The active fractal of `config` for a `width` x `height` image, timed by `clock`.
+pub fn create_fractal(
+    config: &Config,
+    width: u32,
+    height: u32,
+    clock: Box<dyn Clock>,
+) -> Box<dyn Fractal> {
+    let mut fractal: Box<dyn Fractal> = match config.active_config {
+        FractalType::JuliaSet => Box::new(JuliaSet::new(&config.into(), width, height, clock)),
+        FractalType::Mandelbrot => {
+            Box::new(Mandelbrot::new(&config.into(), width, height, clock))
+        }
+        FractalType::BurningShip => {
+            Box::new(BurningShip::new(&config.into(), width, height, clock))
+        }
+        FractalType::Newton => Box::new(Newton::new(&config.into(), width, height, clock)),
+    };
+    fractal.set_renderer(renderer(config));
+    fractal
+}
+
+/// Calculate a whole `width` x `height` image at full resolution in one go.
+pub fn render_image(
+    fractal: &mut dyn Fractal,
+    width: u32,
+    height: u32,
+    mut stats: Option<&mut Stats>,
+) -> IterationBuffer {
+    let mut buffer = IterationBuffer::new(width, height);
+    let mut scheduler = Scheduler::new(width, height, TILE_SIZE, Order::CenterOut, &[1]);
+    while let Some(tile) = scheduler.next() {
+        let points = fractal.calculate(&tile, stats.as_deref_mut());
+        buffer.store(&points);
+        scheduler.complete(&tile);
+    }
+    buffer
+}
+
+#[cfg(test)]
+mod test {
+    use super::{create_fractal, render_image};
+    use crate::{
+        bigfloat::BigComplex, clock::SystemClock, coloring::Coloring, complex::Complex,
+        fractal::IterationBuffer, stats::Stats, util::set_area, Config, FractalType, Renderer,
+    };
+
+    const BACKGROUND: [u8; 4] = [0, 0, 0, 0xFF];
+
+    fn render(config: &Config, width: u32, height: u32) -> IterationBuffer {
+        let mut fractal = create_fractal(config, width, height, Box::new(SystemClock::new()));
+        render_image(fractal.as_mut(), width, height, None)
+    }
+
+    fn small_config(fractal_type: FractalType) -> Config {
+        let mut config = Config {
+            active_config: fractal_type,
+            ..Config::default()
+        };
+        config.mandelbrot_cfg.max_iterations = 50;
+        config.julia_set_cfg.max_iterations = 50;
+        config
+    }
+
+    // escape time of z^2 + c with the radius 2 bailout, without any shortcuts
+    fn reference_iterations(c: Complex, iterations: u32) -> u32 {
+        let mut z = Complex::new(0.0, 0.0);
+        for idx in 1..=iterations {
+            z = z * z + c;
+            if z.square_length() >= 4.0 {
+                return idx;
+            }
+        }
+        iterations + 1
+    }
+
+    #[test]
+    fn test_known_image() {
+        // 8 x 4 pixels of the whole set, '#' marks points inside the set, the real axis from -2
+        // to 1/4 belongs to the set
+        let mut config = small_config(FractalType::Mandelbrot);
+        config.mandelbrot_cfg.c_min = Complex::new(-2.0, -1.0);
+        config.mandelbrot_cfg.c_max = Complex::new(1.0, 1.0);
+        let buffer = render(&config, 8, 4);
+        let image: Vec<String> = buffer
+            .values
+            .chunks(8)
+            .map(|row| {
+                row.iter()
+                    .map(|value| if *value > 50 { '#' } else { '.' })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(image, vec!["........", "....###.", "#######.", "....###."]);
+        assert_eq!(buffer.filled(), 32);
+
+        // inside points get the background, outside points a palette color
+        let rgba = Coloring::new(&config, BACKGROUND).image_to_rgba(&buffer);
+        assert_eq!(rgba.len(), 8 * 4 * 4);
+        assert_eq!(rgba[(2 * 8 + 4) * 4..(2 * 8 + 5) * 4], BACKGROUND);
+        assert_ne!(rgba[0..4], BACKGROUND);
+    }
+
+    #[test]
+    fn test_mandelbrot_reference() {
+        // the escape radius for smooth coloring is larger, escaped points are identified alike,
+        // the area avoids -2 on the border of the set where rounding errors decide
+        let mut config = small_config(FractalType::Mandelbrot);
+        config.mandelbrot_cfg.c_min = Complex::new(-2.1, -1.2);
+        config.mandelbrot_cfg.c_max = Complex::new(0.6, 1.2);
+        config.cardioid_check = false;
+        config.periodicity_check = false;
+        let (width, height) = (40, 30);
+        let buffer = render(&config, width, height);
+        let cfg = &config.mandelbrot_cfg;
+        let scale_real = (cfg.c_max.real() - cfg.c_min.real()) / f64::from(width);
+        let scale_imag = (cfg.c_max.imag() - cfg.c_min.imag()) / f64::from(height);
+        for y in 0..height {
+            for x in 0..width {
+                let c = Complex::new(
+                    f64::from(x).mul_add(scale_real, cfg.c_min.real()),
+                    f64::from(y).mul_add(scale_imag, cfg.c_min.imag()),
+                );
+                let inside = reference_iterations(c, cfg.max_iterations) > cfg.max_iterations;
+                let value = buffer.values[(y * width + x) as usize];
+                assert_eq!(value > cfg.max_iterations, inside, "point {} {}", x, y);
+            }
+        }
+
+        // the shortcuts only skip work
+        let mut shortcuts = config.clone();
+        shortcuts.cardioid_check = true;
+        shortcuts.periodicity_check = true;
+        assert_eq!(render(&shortcuts, width, height).values, buffer.values);
+    }
+
+    #[test]
+    fn test_julia_symmetry() {
+        // z^2 + c is symmetric to the origin, the area is symmetric and the scale exact
+        let mut config = small_config(FractalType::JuliaSet);
+        config.julia_set_cfg.x_min = Complex::new(-1.5, -1.0);
+        config.julia_set_cfg.x_max = Complex::new(1.5, 1.0);
+        let (width, height) = (16, 8);
+        let buffer = render(&config, width, height);
+        for y in 1..height {
+            for x in 1..width {
+                assert_eq!(
+                    buffer.values[(y * width + x) as usize],
+                    buffer.values[((height - y) * width + width - x) as usize]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_deep_zoom() {
+        // 1e-24 wide around the Misiurewicz point i, far below the resolution of f64
+        let big = |real: &str, imag: &str| {
+            BigComplex::new(
+                real.parse().expect("invalid number"),
+                imag.parse().expect("invalid number"),
+            )
+        };
+        let abs_min = big("-0.0000000000000000000000005", "0.9999999999999999999999995");
+        let abs_max = big("0.0000000000000000000000005", "1.0000000000000000000000005");
+        let mut config = small_config(FractalType::Mandelbrot);
+        config.mandelbrot_cfg.max_iterations = 200;
+        let cfg = &mut config.mandelbrot_cfg;
+        set_area(&mut cfg.origin, &mut cfg.c_min, &mut cfg.c_max, &abs_min, &abs_max);
+        let (width, height) = (12, 12);
+        let buffer = render(&config, width, height);
+        let mut values = buffer.values.clone();
+        values.sort_unstable();
+        values.dedup();
+        assert!(values.len() > 8, "{:?}", values);
+
+        // plain f64 corners collapse into a single point
+        let mut plain = config.clone();
+        plain.mandelbrot_cfg.origin = BigComplex::default();
+        plain.mandelbrot_cfg.c_min = abs_min.to_complex();
+        plain.mandelbrot_cfg.c_max = abs_max.to_complex();
+        let buffer = render(&plain, width, height);
+        assert!(buffer.values.iter().all(|value| *value == buffer.values[0]));
+    }
+
+    #[test]
+    fn test_stats() {
+        let config = small_config(FractalType::BurningShip);
+        let mut fractal = create_fractal(&config, 20, 10, Box::new(SystemClock::new()));
+        let mut stats = Stats::new(0.0);
+        let buffer = render_image(fractal.as_mut(), 20, 10, Some(&mut stats));
+        assert_eq!(buffer.filled(), 200);
+        assert!(stats.format_stats().contains("Points:     2.0000E2"));
+    }
+
+    #[test]
+    fn test_subdivision() {
+        // filling the inside of closed borders matches iterating every point
+        for fractal_type in [FractalType::Mandelbrot, FractalType::JuliaSet] {
+            let mut config = small_config(fractal_type);
+            // the Douady rabbit has a large interior
+            config.julia_set_cfg.c = Complex::new(-0.123, 0.745);
+            let mut renderer = config.clone();
+            renderer.renderer = Renderer::Subdivision;
+            let mut fractal = create_fractal(&renderer, 96, 64, Box::new(SystemClock::new()));
+            let mut stats = Stats::new(0.0);
+            let buffer = render_image(fractal.as_mut(), 96, 64, Some(&mut stats));
+            assert!(!stats.format_stats().contains("Filled:     0.0000E0"));
+            assert_eq!(buffer.values, render(&config, 96, 64).values);
+        }
+    }
+
+    #[test]
+    fn test_newton_roots() {
+        // z^3 - 1 converges to one of its roots almost everywhere
+        let buffer = render(&small_config(FractalType::Newton), 12, 8);
+        let roots = buffer.roots.iter().filter(|root| **root < 3).count();
+        assert!(roots > 90);
+    }
+}
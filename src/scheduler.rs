@@ -0,0 +1,215 @@
+use std::{cmp::Ordering, collections::VecDeque};
+
+use super::fractal::Tile;
+
+// edge length of the tiles
+pub const TILE_SIZE: u32 = 64;
+
+/// Block sizes of the passes of a progressive render, the last pass calculates every pixel.
+pub const PASSES: [u32; 4] = [8, 4, 2, 1];
+
+/// Order in which the tiles of an image are calculated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    // tiles closest to the centre of the image first
+    CenterOut,
+    // tiles overlapping the visible part of the image first, both groups centre out from the
+    // middle of the visible part, the end coordinates are exclusive
+    Visible {
+        x_start: u32,
+        y_start: u32,
+        x_end: u32,
+        y_end: u32,
+    },
+}
+
+/// Hands out the tiles of an image in `Order`, in progressive mode once per pass in `PASSES`.
+/// Tiles complete at full resolution are tracked so a stopped render can be resumed.
+pub struct Scheduler {
+    // indexed by id
+    tiles: Vec<Tile>,
+    // tile ids in calculation order
+    order: Vec<usize>,
+    done: Vec<bool>,
+    remaining: usize,
+    queue: VecDeque<Tile>,
+}
+
+impl Scheduler {
+    /// Schedule the tiles of a `width` x `height` image of up to `size` x `size` pixels.
+    /// Every pass in `passes` is calculated for all tiles before the next one starts.
+    pub fn new(width: u32, height: u32, size: u32, order: Order, passes: &[u32]) -> Self {
+        let tiles = Tile::split(width, height, size);
+        let (x_start, y_start, x_end, y_end) = match order {
+            Order::CenterOut => (0, 0, width, height),
+            Order::Visible {
+                x_start,
+                y_start,
+                x_end,
+                y_end,
+            } => (x_start, y_start, x_end, y_end),
+        };
+        let center = (
+            f64::from(x_start + x_end) / 2.0,
+            f64::from(y_start + y_end) / 2.0,
+        );
+        let key = |tile: &Tile| {
+            let visible = tile.x_start < x_end
+                && tile.x_start + tile.width > x_start
+                && tile.y_start < y_end
+                && tile.y_start + tile.height > y_start;
+            let (x, y) = tile.center();
+            (!visible, (x - center.0).hypot(y - center.1))
+        };
+        let mut order: Vec<usize> = (0..tiles.len()).collect();
+        order.sort_by(|a, b| {
+            key(&tiles[*a])
+                .partial_cmp(&key(&tiles[*b]))
+                .unwrap_or(Ordering::Equal)
+        });
+
+        let queue = passes
+            .iter()
+            .flat_map(|block| order.iter().map(move |id| (id, block)))
+            .map(|(id, block)| tiles[*id].with_block(*block))
+            .collect();
+        Self {
+            done: vec![false; tiles.len()],
+            remaining: tiles.len(),
+            tiles,
+            order,
+            queue,
+        }
+    }
+
+    /// All tiles at full resolution, indexed by id.
+    pub fn tiles(&self) -> &[Tile] {
+        &self.tiles
+    }
+
+    pub fn next(&mut self) -> Option<Tile> {
+        self.queue.pop_front()
+    }
+
+    /// Hand out `tile` again before the others, e.g. if it could not be sent to a worker.
+    pub fn requeue(&mut self, tile: Tile) {
+        self.queue.push_front(tile);
+    }
+
+    /// Record a calculated tile. A tile complete at full resolution is not handed out again.
+    pub fn complete(&mut self, tile: &Tile) {
+        if tile.block != 1 || self.done[tile.id] {
+            return;
+        }
+        self.done[tile.id] = true;
+        self.remaining -= 1;
+        self.queue.retain(|queued| queued.id != tile.id);
+    }
+
+    /// Drop all tiles not handed out yet.
+    pub fn cancel(&mut self) {
+        self.queue.clear();
+    }
+
+    /// Hand out all tiles that are not complete again, at full resolution.
+    pub fn resume(&mut self) {
+        let (tiles, done) = (&self.tiles, &self.done);
+        self.queue = self
+            .order
+            .iter()
+            .filter(|id| !done[**id])
+            .map(|id| tiles[*id])
+            .collect();
+    }
+
+    /// True if all tiles are complete at full resolution.
+    pub const fn is_done(&self) -> bool {
+        self.remaining == 0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Order, Scheduler, PASSES};
+
+    #[test]
+    fn test_center_out() {
+        // 3 x 3 tiles, the centre tile comes first and the corners last
+        let mut scheduler = Scheduler::new(30, 30, 10, Order::CenterOut, &[1]);
+        let ids: Vec<usize> = std::iter::from_fn(|| scheduler.next())
+            .map(|tile| tile.id)
+            .collect();
+        assert_eq!(ids, vec![4, 1, 3, 5, 7, 0, 2, 6, 8]);
+    }
+
+    #[test]
+    fn test_visible() {
+        // only the right column is visible
+        let order = Order::Visible {
+            x_start: 25,
+            y_start: 0,
+            x_end: 30,
+            y_end: 30,
+        };
+        let mut scheduler = Scheduler::new(30, 30, 10, order, &[1]);
+        let ids: Vec<usize> = std::iter::from_fn(|| scheduler.next())
+            .map(|tile| tile.id)
+            .collect();
+        assert_eq!(ids[0..3], [5, 2, 8]);
+        assert_eq!(ids[3..6], [4, 1, 7]);
+    }
+
+    #[test]
+    fn test_passes() {
+        let mut scheduler = Scheduler::new(20, 10, 10, Order::CenterOut, &PASSES);
+        let blocks: Vec<u32> = std::iter::from_fn(|| scheduler.next())
+            .map(|tile| tile.block)
+            .collect();
+        assert_eq!(blocks, vec![8, 8, 4, 4, 2, 2, 1, 1]);
+    }
+
+    #[test]
+    fn test_requeue() {
+        let mut scheduler = Scheduler::new(20, 10, 10, Order::CenterOut, &[1]);
+        let first = scheduler.next().expect("tile expected");
+        scheduler.requeue(first);
+        assert_eq!(scheduler.next(), Some(first));
+        assert_ne!(scheduler.next(), Some(first));
+        assert!(scheduler.next().is_none());
+    }
+
+    #[test]
+    fn test_resume() {
+        let mut scheduler = Scheduler::new(20, 20, 10, Order::CenterOut, &[2, 1]);
+        // a tile copied from a known image is complete before it was handed out
+        let known = scheduler.tiles()[3];
+        scheduler.complete(&known);
+
+        let coarse = scheduler.next().expect("tile expected");
+        scheduler.complete(&coarse);
+        assert_eq!(coarse.block, 2);
+        let mut handed_out = 1;
+        while let Some(tile) = scheduler.next() {
+            handed_out += 1;
+            assert_ne!(tile.id, known.id);
+            if tile.block == 1 {
+                scheduler.complete(&tile);
+                break;
+            }
+        }
+        assert_eq!(handed_out, 4);
+        assert!(!scheduler.is_done());
+
+        scheduler.cancel();
+        assert!(scheduler.next().is_none());
+        scheduler.resume();
+        let mut resumed = 0;
+        while let Some(tile) = scheduler.next() {
+            assert_eq!(tile.block, 1);
+            scheduler.complete(&tile);
+            resumed += 1;
+        }
+        assert_eq!(resumed, 2);
+        assert!(scheduler.is_done());
+    }
+}
@@ -1,53 +1,83 @@
-use seed::prelude::web_sys;
 
+use serde::{Deserialize, Serialize};
+
+use super::Renderer;
+
+#[derive(Serialize, Deserialize)]
 pub struct Stats {
     start_time: f64,
     total_time: f64,
     time_in_fractal: f64,
     iterations: usize,
     points: usize,
+    // points filled without iterating
+    filled: usize,
+    short_circuited: usize,
+    renderer: Renderer,
 }
 
 impl Stats {
-    pub fn new() -> Self {
-        let performance = web_sys::window()
-            .expect("Window not found")
-            .performance()
-            .expect("performance should be available");
-
+    /// Start collecting, times are in milliseconds of a `Clock`.
+    pub const fn new(start_time: f64) -> Self {
         Self {
-            start_time: performance.now(),
+            start_time,
             total_time: 0.0,
             time_in_fractal: 0.0,
             iterations: 0,
             points: 0,
+            filled: 0,
+            short_circuited: 0,
+            renderer: Renderer::Scanline,
         }
     }
 
-    pub fn update(&mut self, iterations: usize, points: usize, start_time: f64) {
-        let performance = web_sys::window()
-            .expect("Window not found")
-            .performance()
-            .expect("performance should be available");
-        let end = performance.now();
+    pub fn update(&mut self, iterations: usize, points: usize, start_time: f64, end_time: f64) {
         self.iterations += iterations;
         self.points += points;
-        self.time_in_fractal += end - start_time;
-        self.total_time = end - self.start_time;
+        self.time_in_fractal += end_time - start_time;
+        self.total_time = end_time - self.start_time;
+    }
+
+    /// Add the work done by a web worker. Its time in fractal is added up, on several
+    /// workers it can exceed the total time.
+    pub fn merge(&mut self, other: &Self, now: f64) {
+        self.iterations += other.iterations;
+        self.points += other.points;
+        self.filled += other.filled;
+        self.short_circuited += other.short_circuited;
+        self.time_in_fractal += other.time_in_fractal;
+        self.total_time = now - self.start_time;
+    }
+
+    pub const fn add_filled(&mut self, points: usize) {
+        self.filled += points;
+    }
+
+    pub const fn add_short_circuited(&mut self, points: usize) {
+        self.short_circuited += points;
+    }
+
+    pub const fn set_renderer(&mut self, renderer: Renderer) {
+        self.renderer = renderer;
     }
 
+    #[allow(clippy::cast_precision_loss)]
     pub fn format_stats(&self) -> String {
         format!(
             "\
+Renderer:   {:?}
 Iterations: {:.4E}
 Points:     {:.4E}
+Filled:     {:.4E}
+Shortcuts:  {:.4E}
 Time Calc:  {}
 Tot. Time:  {}
 Iter/Sec:   {:.3}
 Points/Sec: {:.3}
         ",
-            self.iterations, self.points, Stats::format_time(self.time_in_fractal),
-            Stats::format_time(self.total_time),self.iterations as f64/ self.time_in_fractal,
+            self.renderer, self.iterations, self.points, self.filled,
+            self.short_circuited, Self::format_time(self.time_in_fractal),
+            Self::format_time(self.total_time),self.iterations as f64/ self.time_in_fractal,
             self.points as f64 / self.time_in_fractal
         )
     }
@@ -57,7 +87,7 @@ Points/Sec: {:.3}
         let hours = (time_in_secs / 3600.0).floor();
         let minutes = ((time_in_secs % 3600.0) / 60.0).floor();
         let seconds = (time_in_secs % 60.0).floor();
-        format!("{}:{:0>2}:{:0>2}", hours, minutes, seconds)
+        format!("{hours}:{minutes:0>2}:{seconds:0>2}")
  
     }
 }
@@ -0,0 +1,191 @@
+use super::fractal::{Points, Tile};
+
+// rectangles with a side up to this size are calculated point by point
+const MIN_SIZE: u32 = 6;
+
+// pixel rectangle, the end coordinates are exclusive
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Rect {
+    x_start: u32,
+    y_start: u32,
+    x_end: u32,
+    y_end: u32,
+}
+
+impl Rect {
+    const fn width(&self) -> u32 {
+        self.x_end - self.x_start
+    }
+
+    const fn height(&self) -> u32 {
+        self.y_end - self.y_start
+    }
+}
+
+/// Mariani-Silver renderer: a rectangle whose border lies completely inside the set is filled
+/// without iterating its interior, other rectangles are split in halves.
+pub struct Subdivision {
+    points: Points,
+    known: Vec<bool>,
+    stack: Vec<Rect>,
+}
+
+impl Subdivision {
+    /// Calculate the points of `tile`, which must not be a coarse pass. `iterate` calculates the
+    /// iterations and smooth iterations of a point, points with `inside` iterations did not escape.
+    /// Returns the points and the number of points filled without iterating.
+    pub fn calculate(
+        tile: &Tile,
+        inside: u32,
+        mut iterate: impl FnMut(u32, u32) -> (u32, f32),
+    ) -> (Points, usize) {
+        let mut subdivision = Self {
+            points: Points::new(tile.with_block(1)),
+            known: vec![false; tile.len()],
+            stack: vec![Rect {
+                x_start: tile.x_start,
+                y_start: tile.y_start,
+                x_end: tile.x_start + tile.width,
+                y_end: tile.y_start + tile.height,
+            }],
+        };
+        let mut filled = 0;
+        while let Some(rect) = subdivision.stack.pop() {
+            filled += subdivision.process(rect, inside, &mut iterate);
+        }
+        (subdivision.points, filled)
+    }
+
+    const fn index(&self, x: u32, y: u32) -> usize {
+        let tile = &self.points.tile;
+        (y - tile.y_start) as usize * tile.width as usize + (x - tile.x_start) as usize
+    }
+
+    fn point(&mut self, x: u32, y: u32, iterate: &mut impl FnMut(u32, u32) -> (u32, f32)) -> u32 {
+        let idx = self.index(x, y);
+        if !self.known[idx] {
+            let (value, smooth) = iterate(x, y);
+            self.points.values[idx] = value;
+            self.points.smooth[idx] = smooth;
+            self.known[idx] = true;
+        }
+        self.points.values[idx]
+    }
+
+    fn process(
+        &mut self,
+        rect: Rect,
+        inside: u32,
+        iterate: &mut impl FnMut(u32, u32) -> (u32, f32),
+    ) -> usize {
+        if rect.width() <= MIN_SIZE || rect.height() <= MIN_SIZE {
+            for y in rect.y_start..rect.y_end {
+                for x in rect.x_start..rect.x_end {
+                    self.point(x, y, iterate);
+                }
+            }
+            return 0;
+        }
+
+        // all border points are calculated, uniformity is decided afterwards
+        let mut uniform = true;
+        for x in rect.x_start..rect.x_end {
+            uniform &= self.point(x, rect.y_start, iterate) == inside;
+            uniform &= self.point(x, rect.y_end - 1, iterate) == inside;
+        }
+        for y in rect.y_start + 1..rect.y_end - 1 {
+            uniform &= self.point(rect.x_start, y, iterate) == inside;
+            uniform &= self.point(rect.x_end - 1, y, iterate) == inside;
+        }
+
+        if uniform {
+            let mut filled = 0;
+            for y in rect.y_start + 1..rect.y_end - 1 {
+                for x in rect.x_start + 1..rect.x_end - 1 {
+                    let idx = self.index(x, y);
+                    if !self.known[idx] {
+                        self.points.values[idx] = inside;
+                        #[allow(clippy::cast_precision_loss)]
+                        {
+                            self.points.smooth[idx] = inside as f32;
+                        }
+                        self.known[idx] = true;
+                        filled += 1;
+                    }
+                }
+            }
+            return filled;
+        }
+
+        // the halves share the dividing line, its points are only calculated once
+        if rect.width() >= rect.height() {
+            let x_mid = rect.x_start + rect.width() / 2;
+            self.stack.push(Rect {
+                x_end: x_mid + 1,
+                ..rect
+            });
+            self.stack.push(Rect {
+                x_start: x_mid,
+                ..rect
+            });
+        } else {
+            let y_mid = rect.y_start + rect.height() / 2;
+            self.stack.push(Rect {
+                y_end: y_mid + 1,
+                ..rect
+            });
+            self.stack.push(Rect {
+                y_start: y_mid,
+                ..rect
+            });
+        }
+        0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Subdivision;
+    use crate::fractal::{IterationBuffer, Tile};
+
+    const INSIDE: u32 = 100;
+
+    // a disc of inside points surrounded by points escaping after their distance to the centre
+    fn disc(x: u32, y: u32) -> (u32, f32) {
+        let dx = f64::from(x) - 40.0;
+        let dy = f64::from(y) - 40.0;
+        let distance = dx.hypot(dy);
+        if distance < 30.0 {
+            (INSIDE, 100.0)
+        } else {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let value = distance as u32;
+            (value, distance as f32)
+        }
+    }
+
+    #[test]
+    fn test_subdivision() {
+        let (width, height) = (80, 80);
+        let mut buffer = IterationBuffer::new(width, height);
+        let mut calculated = 0;
+        let mut filled = 0;
+        for tile in Tile::split(width, height, 32) {
+            let (points, tile_filled) = Subdivision::calculate(&tile, INSIDE, |x, y| {
+                calculated += 1;
+                disc(x, y)
+            });
+            filled += tile_filled;
+            buffer.store(&points);
+        }
+
+        for y in 0..height {
+            for x in 0..width {
+                assert_eq!(buffer.values[(y * width + x) as usize], disc(x, y).0);
+            }
+        }
+        assert!(filled > 0);
+        assert_eq!(calculated + filled, buffer.values.len());
+        assert_eq!(buffer.filled(), buffer.values.len());
+    }
+}
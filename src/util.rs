@@ -1,123 +1,358 @@
 #![allow(clippy::missing_panics_doc)]
 
-use seed::prelude::web_sys::HtmlInputElement;
-#[allow(clippy::wildcard_imports)]
-use seed::{prelude::*, *};
-
-pub fn set_f64_on_input(name: &str, value: f64) {
-    if let Ok(element) = window()
-        .document()
-        .expect("html document not found")
-        .get_element_by_id(name)
-        .unwrap_or_else(|| panic!("element {} not found", name))
-        .dyn_into::<HtmlInputElement>()
-    {
-        element.set_value(&value.to_string());
+use super::{
+    bigfloat::{BigComplex, BigFloat},
+    complex::Complex,
+};
+
+// escape radius used for smooth coloring, larger values reduce the error of the approximation
+pub const SMOOTH_BAILOUT: f64 = 256.0;
+
+// an orbit returning this close to an earlier point, relative to the pixel size, is periodic
+pub const PERIOD_TOLERANCE: f64 = 1e-3;
+
+// pixels closer than this relative to the coordinates are iterated with arbitrary precision,
+// f64 resolves about 2e-16 and the orbits amplify its rounding errors
+const PRECISE_SCALE: f64 = 1e-14;
+// decimal places kept beyond the pixel size while iterating with arbitrary precision
+const GUARD_DIGITS: usize = 12;
+// areas narrower than this relative to their centre move the centre into the origin
+const REBASE_SCALE: f64 = 1e-6;
+
+/// True if `c` lies in the main cardioid or the period-2 bulb of the Mandelbrot set z^2 + c,
+/// those points never escape.
+#[must_use]
+pub fn in_main_cardioid_or_bulb(c: &Complex) -> bool {
+    let real = c.real() - 0.25;
+    let imag_sqr = c.imag() * c.imag();
+    let q = real.mul_add(real, imag_sqr);
+    if q * (q + real) <= 0.25 * imag_sqr {
+        return true;
     }
+    let real = c.real() + 1.0;
+    real.mul_add(real, imag_sqr) <= 1.0 / 16.0
 }
 
-pub fn set_u32_on_input(name: &str, value: u32) {
-    if let Ok(element) = window()
-        .document()
-        .expect("html document not found")
-        .get_element_by_id(name)
-        .unwrap_or_else(|| panic!("element {} not found", name))
-        .dyn_into::<HtmlInputElement>()
-    {
-        element.set_value(&value.to_string());
-    }
+/// Normalized iteration count for a point that escaped after `iterations` steps,
+/// see <https://en.wikipedia.org/wiki/Plotting_algorithms_for_the_Mandelbrot_set#Continuous_(smooth)_coloring>
+#[must_use]
+#[allow(clippy::cast_possible_truncation)]
+pub fn smooth_iterations(iterations: u32, square_length: f64, exponent: f64) -> f32 {
+    let log_z = square_length.ln() / 2.0;
+    let nu = log_z.log(exponent);
+    f64::max(f64::from(iterations) + 1.0 - nu, 0.0) as f32
 }
 
 #[must_use]
-pub fn get_f64_from_input(name: &str) -> Option<f64> {
-    if let Ok(element) = window()
-        .document()
-        .expect("html document not found")
-        .get_element_by_id(name)
-        .unwrap_or_else(|| panic!("element {} not found", name))
-        .dyn_into::<HtmlInputElement>()
-    {
-        match element.value().parse::<f64>() {
-            Ok(value) => Some(value),
-            Err(err) => {
-                log!(format!("failed to convert {}: {}", name, err));
-                None
-            }
-        }
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub fn integer_exponent(exponent: f64) -> Option<u32> {
+    if exponent.fract() == 0.0 && (0.0..=f64::from(u32::MAX)).contains(&exponent) {
+        Some(exponent as u32)
     } else {
-        log!(format!("failed to retrieve element {}", name));
         None
     }
 }
 
+/// Scale the area spanned by `min` and `max` by `factor` keeping `center` at the same relative position.
 #[must_use]
-pub fn get_u32_from_input(name: &str) -> Option<u32> {
-    if let Ok(element) = window()
-        .document()
-        .expect("html document not found")
-        .get_element_by_id(name)
-        .unwrap_or_else(|| panic!("element {} not found", name))
-        .dyn_into::<HtmlInputElement>()
-    {
-        match element.value().parse::<u32>() {
-            Ok(value) => Some(value),
-            Err(err) => {
-                log!(format!("failed to convert {}: {}", name, err));
-                None
-            }
-        }
+pub fn zoom_area(min: Complex, max: Complex, center: Complex, factor: f64) -> (Complex, Complex) {
+    (
+        Complex::new(
+            (min.real() - center.real()).mul_add(factor, center.real()),
+            (min.imag() - center.imag()).mul_add(factor, center.imag()),
+        ),
+        Complex::new(
+            (max.real() - center.real()).mul_add(factor, center.real()),
+            (max.imag() - center.imag()).mul_add(factor, center.imag()),
+        ),
+    )
+}
+
+/// Height of an image `width` pixels wide which shows the area spanned by `min` and `max`
+/// without distortion. None if the area has no extent in f64 precision.
+#[must_use]
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub fn height_for_ratio(width: u32, min: Complex, max: Complex) -> Option<u32> {
+    let dim = max - min;
+    let height = f64::from(width) * dim.imag() / dim.real();
+    if height.is_finite() && height >= 1.0 && height <= f64::from(u32::MAX) {
+        Some(height as u32)
     } else {
-        log!(format!("failed to retrieve element {}", name));
         None
     }
 }
 
 #[must_use]
-pub fn find_escape_radius(c_norm: f64) -> f64 {
+pub fn find_escape_radius(c_norm: f64, exponent: f64) -> f64 {
+    // Find r with r^d - r >= |c|, for |x| >= r the sequence x^d + c diverges.
+    // r = max(2, |c|) always satisfies this for d >= 2 so it is used as fallback.
+    let fallback = f64::max(2.0, c_norm);
+
     // Newton iteration
-    let mut radius = 2.0;
+    let mut radius = fallback;
 
-    // eprintln!("find_escape_radius({}): c_norm: {}, start: {}", c, c_norm, radius);
     for _idx in 0..20 {
-        let delta_r = radius * radius - radius - c_norm;
+        let delta_r = radius.powf(exponent) - radius - c_norm;
 
         if (0.0..=0.01).contains(&delta_r) {
             break;
         }
 
-        let gradient = 2.0 * radius - 1.0;
+        let gradient = exponent.mul_add(radius.powf(exponent - 1.0), -1.0);
         if gradient == 0.0 {
-            log!("stuck on the zero gradient");
-            radius = 2.0;
+            // stuck on the zero gradient
+            radius = fallback;
             break;
         }
 
         radius -= delta_r / gradient;
     }
 
-    if radius * radius - radius - c_norm >= 0.0 && radius <= 2.0 {
+    if radius.powf(exponent) - radius - c_norm >= 0.0 && radius <= fallback {
         radius
     } else {
-        2.0
+        fallback
+    }
+}
+
+/// Decimal places for iterating points `scale` apart around `center` with arbitrary precision,
+/// `None` if f64 resolves them.
+#[must_use]
+pub fn precise_digits(scale: f64, center: Complex) -> Option<usize> {
+    if scale <= 0.0 || !scale.is_finite() || scale >= f64::max(center.norm(), 1.0) * PRECISE_SCALE {
+        None
+    } else {
+        Some(decimal_places(scale) + GUARD_DIGITS)
+    }
+}
+
+// decimal places needed to tell apart values `distance` apart
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn decimal_places(distance: f64) -> usize {
+    f64::max(-distance.log10().floor(), 0.0) as usize
+}
+
+fn is_deep(min: Complex, max: Complex, center: Complex) -> bool {
+    let width = f64::min(max.real() - min.real(), max.imag() - min.imag());
+    width > 0.0 && width < f64::max(center.norm(), 1.0) * REBASE_SCALE
+}
+
+/// Deep zooms keep their centre in `origin` and the corners `min` and `max` relative to it, so the
+/// corners keep their f64 precision. Shallow areas fold the origin back into the corners.
+pub fn rebase_area(origin: &mut BigComplex, min: &mut Complex, max: &mut Complex) {
+    let center = Complex::new(
+        f64::midpoint(min.real(), max.real()),
+        f64::midpoint(min.imag(), max.imag()),
+    );
+    if !is_deep(*min, *max, origin.approx_complex() + center) {
+        if !origin.is_zero() {
+            *min = (&*origin + &BigComplex::from(*min)).to_complex();
+            *max = (&*origin + &BigComplex::from(*max)).to_complex();
+            *origin = BigComplex::default();
+        }
+        return;
+    }
+
+    let width = f64::min(max.real() - min.real(), max.imag() - min.imag());
+    if center.norm() > width {
+        // the rounded shift keeps the decimal origin short
+        let shift = BigComplex::from(center).round(decimal_places(width) + 3);
+        *origin = &*origin + &shift;
+        let shift = shift.to_complex();
+        *min -= shift;
+        *max -= shift;
+    }
+}
+
+/// Replace the area by the corners `abs_min` and `abs_max` given with arbitrary precision.
+pub fn set_area(
+    origin: &mut BigComplex,
+    min: &mut Complex,
+    max: &mut Complex,
+    abs_min: &BigComplex,
+    abs_max: &BigComplex,
+) {
+    // the corners might round to the same f64, the size does not
+    let size = (abs_max - abs_min).to_complex();
+    if is_deep(Complex::new(0.0, 0.0), size, abs_min.to_complex()) {
+        *origin = abs_min.clone();
+        *min = Complex::new(0.0, 0.0);
+        *max = size;
+        rebase_area(origin, min, max);
+    } else {
+        *origin = BigComplex::default();
+        *min = abs_min.to_complex();
+        *max = abs_max.to_complex();
     }
 }
 
+/// Decimal string of the coordinate `origin` + `value` with enough places for an area `width`
+/// wide, plain f64 formatting without origin.
+#[must_use]
+pub fn format_coordinate(origin: &BigFloat, value: f64, width: f64) -> String {
+    if origin.is_zero() {
+        value.to_string()
+    } else {
+        (origin + &BigFloat::from_f64(value))
+            .round(decimal_places(width) + 6)
+            .to_string()
+    }
+}
 
 #[cfg(test)]
 mod test {
-    use super::find_escape_radius;
-    use crate::complex::Complex;
+    use super::{
+        find_escape_radius, format_coordinate, height_for_ratio, in_main_cardioid_or_bulb,
+        precise_digits, rebase_area, set_area, smooth_iterations, zoom_area,
+    };
+    use crate::{
+        bigfloat::{BigComplex, BigFloat},
+        complex::Complex,
+    };
+
+    #[test]
+    fn test_in_main_cardioid_or_bulb() {
+        assert!(in_main_cardioid_or_bulb(&Complex::new(0.0, 0.0)));
+        assert!(in_main_cardioid_or_bulb(&Complex::new(0.2, 0.5)));
+        assert!(in_main_cardioid_or_bulb(&Complex::new(-0.75, 0.0)));
+        assert!(in_main_cardioid_or_bulb(&Complex::new(-1.2, 0.1)));
+        // inside the set but in a smaller bulb
+        assert!(!in_main_cardioid_or_bulb(&Complex::new(-0.1, 0.9)));
+        assert!(!in_main_cardioid_or_bulb(&Complex::new(0.3, 0.0)));
+        assert!(!in_main_cardioid_or_bulb(&Complex::new(-1.5, 0.0)));
+    }
+
+    #[test]
+    fn test_height_for_ratio() {
+        let min = Complex::new(-2.0, -1.12);
+        let max = Complex::new(0.47, 1.12);
+        assert_eq!(height_for_ratio(1024, min, max), Some(928));
+        assert_eq!(
+            height_for_ratio(300, Complex::new(-1.5, -1.0), Complex::new(1.5, 1.0)),
+            Some(200)
+        );
+        assert_eq!(height_for_ratio(300, min, min), None);
+        assert_eq!(
+            height_for_ratio(300, min, Complex::new(max.real(), min.imag())),
+            None
+        );
+    }
+
+    #[test]
+    fn test_zoom_area() {
+        let min = Complex::new(-2.0, -1.0);
+        let max = Complex::new(2.0, 1.0);
+        let (new_min, new_max) = zoom_area(min, max, Complex::new(1.0, 0.0), 0.5);
+        assert!((new_min.real() + 0.5).abs() < f64::EPSILON);
+        assert!((new_min.imag() + 0.5).abs() < f64::EPSILON);
+        assert!((new_max.real() - 1.5).abs() < f64::EPSILON);
+        assert!((new_max.imag() - 0.5).abs() < f64::EPSILON);
+
+        let (new_min, new_max) = zoom_area(new_min, new_max, Complex::new(1.0, 0.0), 2.0);
+        assert!((new_min.real() - min.real()).abs() < f64::EPSILON);
+        assert!((new_max.imag() - max.imag()).abs() < f64::EPSILON);
+    }
 
     #[test]
     fn test_find_escape_radius() {
         let c_norm = Complex::new(0.3, -0.5).norm();
-        let radius = find_escape_radius(c_norm);
+        let radius = find_escape_radius(c_norm, 2.0);
         assert!(radius * radius - radius >= c_norm);
         assert!(radius * radius - radius - c_norm <= 0.01);
 
         let c_norm = Complex::new(1.0, -1.0).norm();
-        let radius = find_escape_radius(c_norm);
+        let radius = find_escape_radius(c_norm, 2.0);
         assert!(radius * radius - radius >= c_norm);
         assert!(radius * radius - radius - c_norm <= 0.01);
     }
+
+    #[test]
+    fn test_smooth_iterations() {
+        // one more iteration with |x| -> |x|^d has to yield the same value
+        let square_length = 300.0f64 * 300.0;
+        let smooth = smooth_iterations(5, square_length, 2.0);
+        assert!((smooth - smooth_iterations(6, square_length.powi(2), 2.0)).abs() < 1e-4);
+        let smooth = smooth_iterations(5, square_length, 3.0);
+        assert!((smooth - smooth_iterations(6, square_length.powi(3), 3.0)).abs() < 1e-4);
+        assert!(smooth < 6.0);
+    }
+
+    #[test]
+    fn test_find_escape_radius_exponent() {
+        let c_norm = Complex::new(0.3, -0.5).norm();
+        let radius = find_escape_radius(c_norm, 3.0);
+        assert!(radius.powi(3) - radius >= c_norm);
+        assert!(radius.powi(3) - radius - c_norm <= 0.01);
+
+        let c_norm = Complex::new(-0.4, 0.8).norm();
+        let radius = find_escape_radius(c_norm, 2.5);
+        assert!(radius.powf(2.5) - radius >= c_norm);
+        assert!(radius <= 2.0);
+    }
+
+    #[test]
+    fn test_precise_digits() {
+        assert_eq!(precise_digits(1e-3, Complex::new(-0.75, 0.1)), None);
+        assert_eq!(precise_digits(1e-13, Complex::new(-0.75, 0.1)), None);
+        assert_eq!(precise_digits(1e-16, Complex::new(-0.75, 0.1)), Some(28));
+        // relative to the magnitude of the coordinates
+        assert_eq!(precise_digits(1e-13, Complex::new(-100.0, 0.0)), Some(25));
+        assert_eq!(precise_digits(0.0, Complex::new(0.0, 0.0)), None);
+    }
+
+    #[test]
+    fn test_rebase_area() {
+        let big = |real: &str, imag: &str| {
+            BigComplex::new(
+                real.parse().expect("invalid number"),
+                imag.parse().expect("invalid number"),
+            )
+        };
+        let abs_min = big("-0.743643887037158704752191506114774", "0.131825904205311970493132056385139");
+        let abs_max = big("-0.743643887037158704752191506104774", "0.131825904205311970493132056395139");
+        let mut origin = BigComplex::default();
+        let (mut min, mut max) = (Complex::new(0.0, 0.0), Complex::new(0.0, 0.0));
+        set_area(&mut origin, &mut min, &mut max, &abs_min, &abs_max);
+        assert!(!origin.is_zero());
+        assert_eq!(min, Complex::new(0.0, 0.0));
+        let width = max.real() - min.real();
+        assert!((width - 1e-29).abs() < 1e-44);
+        assert_eq!(
+            format_coordinate(origin.real(), min.real(), width),
+            "-0.743643887037158704752191506114774"
+        );
+        assert_eq!(
+            format_coordinate(origin.imag(), max.imag(), width),
+            "0.131825904205311970493132056395139"
+        );
+
+        // panning far keeps the corners close to zero
+        min += Complex::new(5e-29, 0.0);
+        max += Complex::new(5e-29, 0.0);
+        rebase_area(&mut origin, &mut min, &mut max);
+        assert!(min.real().abs() < 1e-29 && max.real().abs() < 1e-29);
+        assert_eq!(
+            format_coordinate(origin.real(), min.real(), width),
+            "-0.743643887037158704752191506064774"
+        );
+
+        // zooming out folds the origin back in
+        let mut min = Complex::new(-0.5, -0.5);
+        let mut max = Complex::new(0.5, 0.5);
+        rebase_area(&mut origin, &mut min, &mut max);
+        assert_eq!(origin, BigComplex::default());
+        assert!((min.real() + 1.243_643_887_037_158_7).abs() < 1e-15);
+        assert_eq!(format_coordinate(&BigFloat::default(), 0.25, 1.0), "0.25");
+
+        // shallow areas stay plain f64
+        set_area(
+            &mut origin,
+            &mut min,
+            &mut max,
+            &BigComplex::from(Complex::new(-2.0, -1.12)),
+            &BigComplex::from(Complex::new(0.47, 1.12)),
+        );
+        assert_eq!(origin, BigComplex::default());
+        assert_eq!((min, max), (Complex::new(-2.0, -1.12), Complex::new(0.47, 1.12)));
+    }
 }
@@ -0,0 +1,452 @@
+use std::collections::BTreeMap;
+
+use super::{
+    bigfloat::{BigComplex, BigFloat},
+    complex::Complex,
+    polynomial::{parse_coefficients, Polynomial},
+    util::set_area,
+    Config, FractalType, MAX_ASPECT_RATIO, MAX_ITERATIONS, MIN_EXPONENT, NEWTON_MAX_DEGREE,
+    NEWTON_MIN_DEGREE,
+};
+
+// editor field names, the input ids are these prefixed with the fractal type
+pub const ITERATIONS: &str = "iterations";
+pub const MAX_REAL: &str = "max_real";
+pub const MIN_REAL: &str = "min_real";
+pub const MAX_IMAG: &str = "max_imag";
+pub const MIN_IMAG: &str = "min_imag";
+pub const C_REAL: &str = "c_real";
+pub const C_IMAG: &str = "c_imag";
+pub const EXPONENT: &str = "exponent";
+pub const COEFFICIENTS: &str = "coefficients";
+
+/// Raw editor values by field name.
+pub type Inputs = BTreeMap<&'static str, String>;
+/// Error messages by field name.
+pub type Errors = BTreeMap<&'static str, String>;
+
+/// Check all editor fields of `fractal_type` and apply them to `config`.
+/// `config` is left unchanged if any field is invalid.
+pub fn validate(
+    fractal_type: FractalType,
+    inputs: &Inputs,
+    config: &mut Config,
+) -> Result<(), Errors> {
+    let mut validator = Validator {
+        inputs,
+        errors: Errors::new(),
+    };
+    let iterations = validator.iterations();
+    let area = validator.area();
+
+    match fractal_type {
+        FractalType::JuliaSet => {
+            let c_real = validator.number(C_REAL);
+            let c_imag = validator.number(C_IMAG);
+            let exponent = validator.exponent();
+            if let (
+                Some(iterations),
+                Some((min, max)),
+                Some(c_real),
+                Some(c_imag),
+                Some(exponent),
+            ) = (iterations, area, c_real, c_imag, exponent)
+            {
+                let cfg = &mut config.julia_set_cfg;
+                cfg.max_iterations = iterations;
+                set_area(&mut cfg.origin, &mut cfg.x_min, &mut cfg.x_max, &min, &max);
+                cfg.c = Complex::new(c_real, c_imag);
+                cfg.exponent = exponent;
+            }
+        }
+        FractalType::Mandelbrot => {
+            let exponent = validator.exponent();
+            if let (Some(iterations), Some((min, max)), Some(exponent)) =
+                (iterations, area, exponent)
+            {
+                let cfg = &mut config.mandelbrot_cfg;
+                cfg.max_iterations = iterations;
+                set_area(&mut cfg.origin, &mut cfg.c_min, &mut cfg.c_max, &min, &max);
+                cfg.exponent = exponent;
+            }
+        }
+        FractalType::BurningShip => {
+            let area = validator.f64_area(area);
+            if let (Some(iterations), Some((min, max))) = (iterations, area) {
+                let cfg = &mut config.burning_ship_cfg;
+                cfg.max_iterations = iterations;
+                cfg.c_min = min;
+                cfg.c_max = max;
+            }
+        }
+        FractalType::Newton => {
+            let coefficients = validator.coefficients();
+            let area = validator.f64_area(area);
+            if let (Some(iterations), Some((min, max)), Some(coefficients)) =
+                (iterations, area, coefficients)
+            {
+                let cfg = &mut config.newton_cfg;
+                cfg.max_iterations = iterations;
+                cfg.x_min = min;
+                cfg.x_max = max;
+                cfg.coefficients = coefficients;
+            }
+        }
+    }
+
+    if validator.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(validator.errors)
+    }
+}
+
+/// Check the parameters and area of `fractal_type` in a config that did not pass the editor,
+/// e.g. one read from a link, an image or a bookmark. Returns the first invalid field.
+pub fn check(config: &Config, fractal_type: FractalType) -> Result<(), String> {
+    let field_error = |field: &str, message: String| format!("invalid {field}: {message}");
+    let (iterations, min, max) = match fractal_type {
+        FractalType::JuliaSet => {
+            let cfg = &config.julia_set_cfg;
+            if !cfg.c.real().is_finite() || !cfg.c.imag().is_finite() {
+                return Err(field_error("c", "must be a number".to_string()));
+            }
+            check_exponent(cfg.exponent).map_err(|message| field_error(EXPONENT, message))?;
+            (cfg.max_iterations, cfg.x_min, cfg.x_max)
+        }
+        FractalType::Mandelbrot => {
+            let cfg = &config.mandelbrot_cfg;
+            check_exponent(cfg.exponent).map_err(|message| field_error(EXPONENT, message))?;
+            (cfg.max_iterations, cfg.c_min, cfg.c_max)
+        }
+        FractalType::BurningShip => {
+            let cfg = &config.burning_ship_cfg;
+            (cfg.max_iterations, cfg.c_min, cfg.c_max)
+        }
+        FractalType::Newton => {
+            let cfg = &config.newton_cfg;
+            if !cfg.coefficients.iter().all(|coeff| coeff.is_finite()) {
+                return Err(field_error(COEFFICIENTS, "must be numbers".to_string()));
+            }
+            check_degree(&cfg.coefficients)
+                .map_err(|message| field_error(COEFFICIENTS, message))?;
+            (cfg.max_iterations, cfg.x_min, cfg.x_max)
+        }
+    };
+    check_iterations(iterations).map_err(|message| field_error(ITERATIONS, message))?;
+
+    let (width, height) = (max.real() - min.real(), max.imag() - min.imag());
+    // also rejects NaN
+    if !(width > 0.0 && height > 0.0) {
+        return Err(format!("invalid area: {min} - {max}"));
+    }
+    check_ratio(width, height).map_err(|message| field_error("area", message))
+}
+
+fn iterations_error() -> String {
+    format!("must be a whole number from 1 to {MAX_ITERATIONS}")
+}
+
+fn check_iterations(iterations: u32) -> Result<(), String> {
+    if (1..=MAX_ITERATIONS).contains(&iterations) {
+        Ok(())
+    } else {
+        Err(iterations_error())
+    }
+}
+
+fn check_exponent(exponent: f64) -> Result<(), String> {
+    if exponent.is_finite() && exponent >= MIN_EXPONENT {
+        Ok(())
+    } else {
+        Err(format!("must be at least {MIN_EXPONENT}"))
+    }
+}
+
+fn check_degree(coefficients: &[f64]) -> Result<(), String> {
+    let degree = Polynomial::new(coefficients).degree();
+    if (NEWTON_MIN_DEGREE..=NEWTON_MAX_DEGREE).contains(&degree) {
+        Ok(())
+    } else {
+        Err(format!(
+            "degree is {degree}, must be from {NEWTON_MIN_DEGREE} to {NEWTON_MAX_DEGREE}"
+        ))
+    }
+}
+
+// the aspect ratio of the area determines the canvas height
+fn check_ratio(width: f64, height: f64) -> Result<(), String> {
+    let ratio = height / width;
+    if ratio.is_finite() && (1.0 / MAX_ASPECT_RATIO..=MAX_ASPECT_RATIO).contains(&ratio) {
+        Ok(())
+    } else {
+        Err(format!(
+            "height must be between 1/{MAX_ASPECT_RATIO} and {MAX_ASPECT_RATIO} times the width"
+        ))
+    }
+}
+
+// collects the first error per field, the getters return None for invalid fields
+struct Validator<'a> {
+    inputs: &'a Inputs,
+    errors: Errors,
+}
+
+impl<'a> Validator<'a> {
+    fn value(&self, field: &'static str) -> &'a str {
+        self.inputs.get(field).map_or("", |value| value.trim())
+    }
+
+    fn error(&mut self, field: &'static str, message: String) {
+        self.errors.entry(field).or_insert(message);
+    }
+
+    fn iterations(&mut self) -> Option<u32> {
+        match self.value(ITERATIONS).parse::<u32>() {
+            Ok(iterations) if check_iterations(iterations).is_ok() => Some(iterations),
+            _ => {
+                self.error(ITERATIONS, iterations_error());
+                None
+            }
+        }
+    }
+
+    fn number(&mut self, field: &'static str) -> Option<f64> {
+        match self.value(field).parse::<f64>() {
+            Ok(value) if value.is_finite() => Some(value),
+            _ => {
+                self.error(field, "must be a number".to_string());
+                None
+            }
+        }
+    }
+
+    // area corners keep all their decimal places for deep zooms
+    fn coordinate(&mut self, field: &'static str) -> Option<BigFloat> {
+        match self.value(field).parse::<BigFloat>() {
+            Ok(value) if value.to_f64().is_finite() => Some(value),
+            _ => {
+                self.error(field, "must be a number".to_string());
+                None
+            }
+        }
+    }
+
+    fn exponent(&mut self) -> Option<f64> {
+        let exponent = self.number(EXPONENT)?;
+        match check_exponent(exponent) {
+            Ok(()) => Some(exponent),
+            Err(message) => {
+                self.error(EXPONENT, message);
+                None
+            }
+        }
+    }
+
+    fn coefficients(&mut self) -> Option<Vec<f64>> {
+        let coefficients = match parse_coefficients(self.value(COEFFICIENTS)) {
+            Some(coefficients) if coefficients.iter().all(|coeff| coeff.is_finite()) => {
+                coefficients
+            }
+            _ => {
+                self.error(
+                    COEFFICIENTS,
+                    "must be a comma separated list of numbers".to_string(),
+                );
+                return None;
+            }
+        };
+        match check_degree(&coefficients) {
+            Ok(()) => Some(coefficients),
+            Err(message) => {
+                self.error(COEFFICIENTS, message);
+                None
+            }
+        }
+    }
+
+    // fractal types without an origin keep the corners in f64 precision,
+    // the area must not collapse when they are rounded
+    fn f64_area(&mut self, area: Option<(BigComplex, BigComplex)>) -> Option<(Complex, Complex)> {
+        let (min, max) = area?;
+        let (min, max) = (min.to_complex(), max.to_complex());
+        let (width, height) = (max.real() - min.real(), max.imag() - min.imag());
+        if width <= 0.0 || height <= 0.0 {
+            self.error(
+                MIN_REAL,
+                "the corners are too close, this fractal type does not zoom this deep".to_string(),
+            );
+            return None;
+        }
+        if let Err(message) = check_ratio(width, height) {
+            self.error(MAX_IMAG, message);
+            return None;
+        }
+        Some((min, max))
+    }
+
+    // the area must not be empty and its aspect ratio determines the canvas height
+    fn area(&mut self) -> Option<(BigComplex, BigComplex)> {
+        let max_real = self.coordinate(MAX_REAL);
+        let min_real = self.coordinate(MIN_REAL);
+        let max_imag = self.coordinate(MAX_IMAG);
+        let min_imag = self.coordinate(MIN_IMAG);
+        let (max_real, min_real, max_imag, min_imag) = (max_real?, min_real?, max_imag?, min_imag?);
+
+        let width = (&max_real - &min_real).to_f64();
+        let height = (&max_imag - &min_imag).to_f64();
+        if width <= 0.0 {
+            self.error(MIN_REAL, "must be less than Max. Real".to_string());
+        }
+        if height <= 0.0 {
+            self.error(MIN_IMAG, "must be less than Max. Imag.".to_string());
+        }
+        if width <= 0.0 || height <= 0.0 {
+            return None;
+        }
+
+        if let Err(message) = check_ratio(width, height) {
+            self.error(MAX_IMAG, message);
+            return None;
+        }
+        Some((
+            BigComplex::new(min_real, min_imag),
+            BigComplex::new(max_real, max_imag),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        check, validate, Inputs, COEFFICIENTS, C_IMAG, C_REAL, EXPONENT, ITERATIONS, MAX_IMAG,
+        MAX_REAL, MIN_IMAG, MIN_REAL,
+    };
+    use crate::{complex::Complex, Config, FractalType};
+
+    fn julia_inputs() -> Inputs {
+        vec![
+            (ITERATIONS, "500"),
+            (C_REAL, "-0.7"),
+            (C_IMAG, "0.27"),
+            (EXPONENT, "3"),
+            (MAX_REAL, "1.0"),
+            (MIN_REAL, "-1.0"),
+            (MAX_IMAG, "0.5"),
+            (MIN_IMAG, "-0.5"),
+        ]
+        .into_iter()
+        .map(|(field, value)| (field, value.to_string()))
+        .collect()
+    }
+
+    #[test]
+    fn test_validate_julia() {
+        let mut config = Config::default();
+        assert!(validate(FractalType::JuliaSet, &julia_inputs(), &mut config).is_ok());
+        assert_eq!(config.julia_set_cfg.max_iterations, 500);
+        assert_eq!(config.julia_set_cfg.c, Complex::new(-0.7, 0.27));
+        assert!((config.julia_set_cfg.exponent - 3.0).abs() < f64::EPSILON);
+        assert_eq!(config.julia_set_cfg.x_min, Complex::new(-1.0, -0.5));
+        assert_eq!(config.julia_set_cfg.x_max, Complex::new(1.0, 0.5));
+    }
+
+    #[test]
+    fn test_invalid_fields() {
+        let mut inputs = julia_inputs();
+        inputs.insert(ITERATIONS, "0".to_string());
+        inputs.insert(C_REAL, "abc".to_string());
+        inputs.insert(EXPONENT, "1.5".to_string());
+        inputs.insert(MIN_REAL, "1.0".to_string());
+
+        let mut config = Config::default();
+        let errors = validate(FractalType::JuliaSet, &inputs, &mut config).unwrap_err();
+        assert_eq!(
+            errors.keys().copied().collect::<Vec<&str>>(),
+            vec![C_REAL, EXPONENT, ITERATIONS, MIN_REAL]
+        );
+        // nothing is applied
+        assert_eq!(config.julia_set_cfg, Config::default().julia_set_cfg);
+    }
+
+    #[test]
+    fn test_invalid_area() {
+        let mut config = Config::default();
+
+        let mut inputs = julia_inputs();
+        inputs.insert(MIN_IMAG, "0.5".to_string());
+        let errors = validate(FractalType::JuliaSet, &inputs, &mut config).unwrap_err();
+        assert!(errors.contains_key(MIN_IMAG));
+
+        let mut inputs = julia_inputs();
+        inputs.insert(MAX_IMAG, "100".to_string());
+        let errors = validate(FractalType::JuliaSet, &inputs, &mut config).unwrap_err();
+        assert!(errors.contains_key(MAX_IMAG));
+
+        let mut inputs = julia_inputs();
+        inputs.remove(MAX_REAL);
+        let errors = validate(FractalType::JuliaSet, &inputs, &mut config).unwrap_err();
+        assert!(errors.contains_key(MAX_REAL));
+    }
+
+    #[test]
+    fn test_f64_area() {
+        let mut config = Config::default();
+        let mut inputs = julia_inputs();
+        inputs.insert(MIN_REAL, "1.00000000000000000001".to_string());
+        inputs.insert(MAX_REAL, "1.00000000000000000002".to_string());
+        inputs.insert(MIN_IMAG, "1.00000000000000000001".to_string());
+        inputs.insert(MAX_IMAG, "1.00000000000000000002".to_string());
+        let errors = validate(FractalType::BurningShip, &inputs, &mut config).unwrap_err();
+        assert!(errors.contains_key(MIN_REAL));
+        assert_eq!(config.burning_ship_cfg, Config::default().burning_ship_cfg);
+        inputs.insert(COEFFICIENTS, "1, 0, -1".to_string());
+        assert!(validate(FractalType::Newton, &inputs, &mut config).is_err());
+        // deep zooms keep the corners apart
+        assert!(validate(FractalType::Mandelbrot, &inputs, &mut config).is_ok());
+    }
+
+    #[test]
+    fn test_validate_newton() {
+        let mut config = Config::default();
+        let mut inputs = julia_inputs();
+        inputs.insert(COEFFICIENTS, "1, 0, 0, 0, -1".to_string());
+        assert!(validate(FractalType::Newton, &inputs, &mut config).is_ok());
+        assert_eq!(
+            config.newton_cfg.coefficients,
+            vec![1.0, 0.0, 0.0, 0.0, -1.0]
+        );
+
+        inputs.insert(COEFFICIENTS, "1, 2".to_string());
+        let errors = validate(FractalType::Newton, &inputs, &mut config).unwrap_err();
+        assert!(errors.contains_key(COEFFICIENTS));
+    }
+
+    #[test]
+    fn test_check() {
+        let config = Config::default();
+        for fractal_type in [
+            FractalType::Mandelbrot,
+            FractalType::JuliaSet,
+            FractalType::BurningShip,
+            FractalType::Newton,
+        ] {
+            assert!(check(&config, fractal_type).is_ok());
+        }
+
+        let mut config = Config::default();
+        config.mandelbrot_cfg.max_iterations = 0;
+        assert!(check(&config, FractalType::Mandelbrot).is_err());
+        // other fractal types are not affected
+        assert!(check(&config, FractalType::JuliaSet).is_ok());
+
+        let mut config = Config::default();
+        config.burning_ship_cfg.c_max = Complex::new(1e-9, 1.0);
+        config.burning_ship_cfg.c_min = Complex::new(0.0, 0.0);
+        assert!(check(&config, FractalType::BurningShip).is_err());
+
+        let mut config = Config::default();
+        config.newton_cfg.coefficients = vec![1.0, 2.0];
+        assert!(check(&config, FractalType::Newton).is_err());
+    }
+}
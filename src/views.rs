@@ -1,20 +1,32 @@
 #[allow(clippy::wildcard_imports)]
 use seed::{prelude::*, *};
 
-use super::{FractalType, Model, Msg};
+use super::{
+    bookmarks::Bookmark,
+    palette::{rgb_to_html, ColorStop, Interpolation, MIN_STOPS},
+    validation,
+    FractalType, Model, Msg, Renderer,
+};
+use seed::prelude::web_sys::HtmlInputElement;
 
 pub fn view(model: &Model) -> Node<Msg> {
     div![
         C!["outer_cntr"],
         IF![model.config.active_config == FractalType::Mandelbrot => h1!["Mandelbrot Set"]],
         IF![model.config.active_config == FractalType::JuliaSet => h1!["Julia Set"]],
+        IF![model.config.active_config == FractalType::BurningShip => h1!["Burning Ship"]],
+        IF![model.config.active_config == FractalType::Newton => h1!["Newton Fractal"]],
         div![
             C!["inner_cntr"],
             view_buttons(model),
             div![
                 C!["fractal_container"],
-                view_julia_set_cfg_editor(),
-                view_mandelbrot_cfg_editor(),
+                view_julia_set_cfg_editor(model),
+                view_mandelbrot_cfg_editor(model),
+                view_burning_ship_cfg_editor(model),
+                view_newton_cfg_editor(model),
+                view_palette_editor(model),
+                view_bookmarks(model),
                 div![
                     C!["canvas_cntr"],
                     canvas![
@@ -25,11 +37,17 @@ pub fn view(model: &Model) -> Node<Msg> {
                             At::Height => model.height.to_string()
                         },
                         "Your browser does not support the canvas tag.",
-                        IF!(model.edit_mode =>
-                                ev(Ev::MouseDown, |event| {
-                                    let mouse_event: web_sys::MouseEvent = event.unchecked_into();
-                                    Msg::MouseDown(mouse_event)})
+                        IF!(!model.edit_mode =>
+                            ev(Ev::Wheel, |event| {
+                                event.prevent_default();
+                                let wheel_event: web_sys::WheelEvent = event.unchecked_into();
+                                Msg::Wheel(wheel_event)
+                            })
                         ),
+                        ev(Ev::MouseDown, |event| {
+                            let mouse_event: web_sys::MouseEvent = event.unchecked_into();
+                            Msg::MouseDown(mouse_event)
+                        }),
                         IF!(model.mouse_drag.is_some() =>
                             vec![
                                 ev(Ev::MouseMove, |event| {
@@ -49,6 +67,7 @@ pub fn view(model: &Model) -> Node<Msg> {
     ]
 }
 
+#[allow(clippy::too_many_lines)]
 fn view_buttons(model: &Model) -> Vec<Node<Msg>> {
     vec![div![
         C!["button_cntr"],
@@ -73,6 +92,85 @@ fn view_buttons(model: &Model) -> Vec<Node<Msg>> {
             ev(Ev::Click, |_| Msg::Clear),
             "Clear"
         ],
+        button![
+            C!["menu_button"],
+            id!("recolor"),
+            ev(Ev::Click, |_| Msg::Recolor),
+            IF!(!model.paused =>  attrs!{At::Disabled => "true" } ),
+            IF!(model.buffer.is_empty() =>  attrs!{At::Disabled => "true" } ),
+            "Recolor"
+        ],
+        button![
+            C!["menu_button"],
+            id!("export_png"),
+            ev(Ev::Click, |_| Msg::ExportPng),
+            IF!(!model.paused =>  attrs!{At::Disabled => "true" } ),
+            IF!(model.buffer.is_empty() =>  attrs!{At::Disabled => "true" } ),
+            "Export PNG"
+        ],
+        label![
+            C!["type_select_label"],
+            attrs! { At::For => "import_png"},
+            "Import PNG"
+        ],
+        input![
+            C!["input"],
+            id!("import_png"),
+            attrs! {
+                At::Type => "file",
+                At::Accept => ".png,image/png",
+            },
+            IF!(!model.paused =>  attrs!{At::Disabled => "true" } ),
+            IF!(model.edit_mode =>  attrs!{At::Disabled => "true" } ),
+            ev(Ev::Change, |event| {
+                let input = event
+                    .target()
+                    .and_then(|target| target.dyn_into::<HtmlInputElement>().ok());
+                let file = input
+                    .as_ref()
+                    .and_then(HtmlInputElement::files)
+                    .and_then(|files| files.get(0));
+                // allow importing the same file again
+                if let Some(input) = input {
+                    input.set_value("");
+                }
+                Msg::ImportPng(file)
+            }),
+        ],
+        button![
+            C!["menu_button"],
+            id!("copy_clipboard"),
+            ev(Ev::Click, |_| Msg::CopyToClipboard),
+            IF!(!model.paused =>  attrs!{At::Disabled => "true" } ),
+            IF!(model.buffer.is_empty() =>  attrs!{At::Disabled => "true" } ),
+            IF!(model.clipboard_denied => attrs!{
+                At::Disabled => "true",
+                At::Title => "Permission to write to the clipboard was denied",
+            }),
+            "Copy Image"
+        ],
+        IF!(!model.status_text.is_empty() => p![C!["hint_text"], model.status_text.as_str()]),
+        div![
+            C!["edit_button_cntr"],
+            button![
+                C!["menu_button"],
+                id!("back"),
+                attrs! {At::Title => "Ctrl+Z"},
+                ev(Ev::Click, |_| Msg::Back),
+                IF!(model.edit_mode =>  attrs!{At::Disabled => "true" } ),
+                IF!(!model.history.can_back(model.config.active_config) =>  attrs!{At::Disabled => "true" } ),
+                "Back"
+            ],
+            button![
+                C!["menu_button"],
+                id!("forward"),
+                attrs! {At::Title => "Ctrl+Y"},
+                ev(Ev::Click, |_| Msg::Forward),
+                IF!(model.edit_mode =>  attrs!{At::Disabled => "true" } ),
+                IF!(!model.history.can_forward(model.config.active_config) =>  attrs!{At::Disabled => "true" } ),
+                "Forward"
+            ],
+        ],
         button![
             C!["menu_button"],
             id!("edit"),
@@ -80,6 +178,20 @@ fn view_buttons(model: &Model) -> Vec<Node<Msg>> {
             IF!(model.edit_mode =>  attrs!{At::Disabled => "true" } ),
             "Edit"
         ],
+        button![
+            C!["menu_button"],
+            id!("palette"),
+            ev(Ev::Click, |_| Msg::EditPalette),
+            IF!(model.palette_edit.is_some() =>  attrs!{At::Disabled => "true" } ),
+            "Palette"
+        ],
+        button![
+            C!["menu_button"],
+            id!("bookmarks"),
+            ev(Ev::Click, |_| Msg::ToggleBookmarks),
+            IF!(model.view_bookmarks => "Hide Bookmarks"),
+            IF!(!model.view_bookmarks => "Bookmarks"),
+        ],
         label![
             C!["type_select_label"],
             attrs! { At::For => "type_select"},
@@ -91,11 +203,15 @@ fn view_buttons(model: &Model) -> Vec<Node<Msg>> {
             attrs! {At::Name => "type_select" },
             IF![model.config.active_config == FractalType::Mandelbrot => attrs!{At::Value => "type_mandelbrot"}],
             IF![model.config.active_config == FractalType::JuliaSet => attrs!{At::Value => "type_julia_set"}],
+            IF![model.config.active_config == FractalType::BurningShip => attrs!{At::Value => "type_burning_ship"}],
+            IF![model.config.active_config == FractalType::Newton => attrs!{At::Value => "type_newton"}],
             option![attrs! {At::Value => "type_mandelbrot" }, "Mandelbrot Set"],
             option![attrs! {At::Value => "type_julia_set" }, "Julia Set"],
+            option![attrs! {At::Value => "type_burning_ship" }, "Burning Ship"],
+            option![attrs! {At::Value => "type_newton" }, "Newton Fractal"],
             IF!(!model.paused =>  attrs!{At::Disabled => "true" } ),
             IF!(model.edit_mode =>  attrs!{At::Disabled => "true" } ),
-            ev(Ev::Change, |_| Msg::TypeChanged),
+            input_ev(Ev::Change, Msg::TypeChanged),
         ],
         div![
             C!["cb_stats_cntr"],
@@ -113,7 +229,118 @@ fn view_buttons(model: &Model) -> Vec<Node<Msg>> {
                 },
                 IF!(!model.paused => attrs!{ At::Disabled => "" }),
                 IF!(model.config.view_stats => attrs!{ At::Checked => "1" }),
-                ev(Ev::Change, |_| Msg::StatsChanged),
+                ev(Ev::Change, |event| Msg::StatsChanged(is_checked(&event))),
+            ]
+        ],
+        div![
+            C!["cb_smooth_cntr"],
+            label![
+                C!["type_select_label"],
+                attrs! { At::For => "smooth_cb"},
+                "Smooth Colors"
+            ],
+            input![
+                C!["smooth_cb"],
+                id!("smooth_cb"),
+                attrs! {
+                    At::Name => "smooth_cb",
+                    At::Type => "checkbox",
+                },
+                IF!(!model.paused => attrs!{ At::Disabled => "" }),
+                IF!(model.config.smooth_coloring => attrs!{ At::Checked => "1" }),
+                ev(Ev::Change, |event| Msg::SmoothChanged(is_checked(&event))),
+            ]
+        ],
+        div![
+            C!["cb_progressive_cntr"],
+            label![
+                C!["type_select_label"],
+                attrs! { At::For => "progressive_cb"},
+                "Progressive"
+            ],
+            input![
+                C!["progressive_cb"],
+                id!("progressive_cb"),
+                attrs! {
+                    At::Name => "progressive_cb",
+                    At::Type => "checkbox",
+                },
+                IF!(!model.paused => attrs!{ At::Disabled => "" }),
+                IF!(model.config.progressive => attrs!{ At::Checked => "1" }),
+                ev(Ev::Change, |event| Msg::ProgressiveChanged(is_checked(&event))),
+            ]
+        ],
+        label![
+            C!["type_select_label"],
+            attrs! { At::For => "renderer_select"},
+            "Renderer"
+        ],
+        select![
+            C!["type_select"],
+            id!("renderer_select"),
+            attrs! {At::Name => "renderer_select" },
+            IF![model.config.renderer == Renderer::Scanline => attrs!{At::Value => "renderer_scanline"}],
+            IF![model.config.renderer == Renderer::Subdivision => attrs!{At::Value => "renderer_subdivision"}],
+            option![attrs! {At::Value => "renderer_scanline" }, "Scanline"],
+            option![attrs! {At::Value => "renderer_subdivision" }, "Subdivision"],
+            IF!(!model.paused =>  attrs!{At::Disabled => "true" } ),
+            input_ev(Ev::Change, Msg::RendererChanged),
+        ],
+        div![
+            C!["cb_cardioid_cntr"],
+            label![
+                C!["type_select_label"],
+                attrs! { At::For => "cardioid_cb"},
+                "Cardioid Check"
+            ],
+            input![
+                C!["cardioid_cb"],
+                id!("cardioid_cb"),
+                attrs! {
+                    At::Name => "cardioid_cb",
+                    At::Type => "checkbox",
+                },
+                IF!(!model.paused => attrs!{ At::Disabled => "" }),
+                IF!(model.config.cardioid_check => attrs!{ At::Checked => "1" }),
+                ev(Ev::Change, |event| Msg::CardioidCheckChanged(is_checked(&event))),
+            ]
+        ],
+        div![
+            C!["cb_periodicity_cntr"],
+            label![
+                C!["type_select_label"],
+                attrs! { At::For => "periodicity_cb"},
+                "Periodicity Check"
+            ],
+            input![
+                C!["periodicity_cb"],
+                id!("periodicity_cb"),
+                attrs! {
+                    At::Name => "periodicity_cb",
+                    At::Type => "checkbox",
+                },
+                IF!(!model.paused => attrs!{ At::Disabled => "" }),
+                IF!(model.config.periodicity_check => attrs!{ At::Checked => "1" }),
+                ev(Ev::Change, |event| Msg::PeriodicityCheckChanged(is_checked(&event))),
+            ]
+        ],
+        div![
+            C!["zoom_factor_cntr"],
+            label![
+                C!["type_select_label"],
+                attrs! { At::For => "zoom_factor"},
+                "Zoom Factor"
+            ],
+            input![
+                C!["zoom_factor"],
+                id!("zoom_factor"),
+                attrs! {
+                    At::Type => "number",
+                    At::Min => "1.1",
+                    At::Step => "0.1",
+                    At::Value => model.config.zoom_factor.to_string(),
+                },
+                input_ev(Ev::Change, Msg::ZoomFactorChanged),
             ]
         ],
         div![
@@ -124,7 +351,7 @@ fn view_buttons(model: &Model) -> Vec<Node<Msg>> {
                 attrs! {
                    // At::Value => model.stats_text,
                    At::ReadOnly => "true",
-                   At::Rows => "6",
+                   At::Rows => "8",
                    At::Placeholder => "No Stats yet"
                 },
                 model.stats_text.as_str()
@@ -134,9 +361,9 @@ fn view_buttons(model: &Model) -> Vec<Node<Msg>> {
 }
 
 #[allow(clippy::too_many_lines)]
-fn view_julia_set_cfg_editor() -> Node<Msg> {
+fn view_julia_set_cfg_editor(model: &Model) -> Node<Msg> {
     div![
-        C!["edit_cntr_hidden"],
+        C![editor_class(model, FractalType::JuliaSet)],
         id!("julia_edit_cntr"),
         div![
             C!["input_cntr"],
@@ -155,6 +382,8 @@ fn view_julia_set_cfg_editor() -> Node<Msg> {
                 input![
                     C!["input"],
                     id!("julia_iterations"),
+                    attrs! { At::Value => model.editor.value(validation::ITERATIONS) },
+                    input_ev(Ev::Input, |value| Msg::InputChanged(validation::ITERATIONS, value)),
                     attrs! {
                         At::Name => "julia_iterations",
                         At::Type => "number",
@@ -163,6 +392,7 @@ fn view_julia_set_cfg_editor() -> Node<Msg> {
                         // At::Value => {model.max_iterations.to_string()},
                     },
                 ],
+                view_field_error(model, validation::ITERATIONS),
             ],
             div![
                 C!["input_inner"],
@@ -174,6 +404,8 @@ fn view_julia_set_cfg_editor() -> Node<Msg> {
                 input![
                     C!["input"],
                     id!("julia_c_real"),
+                    attrs! { At::Value => model.editor.value(validation::C_REAL) },
+                    input_ev(Ev::Input, |value| Msg::InputChanged(validation::C_REAL, value)),
                     attrs! {
                         At::Name => "julia_c_real",
                         At::Type => "number",
@@ -181,6 +413,7 @@ fn view_julia_set_cfg_editor() -> Node<Msg> {
                         //At::Value => {model.c_real.to_string()},
                     },
                 ],
+                view_field_error(model, validation::C_REAL),
             ],
             div![
                 C!["input_inner"],
@@ -192,6 +425,8 @@ fn view_julia_set_cfg_editor() -> Node<Msg> {
                 input![
                     C!["input"],
                     id!("julia_c_imag"),
+                    attrs! { At::Value => model.editor.value(validation::C_IMAG) },
+                    input_ev(Ev::Input, |value| Msg::InputChanged(validation::C_IMAG, value)),
                     attrs! {
                         At::Name => "julia_c_imag",
                         At::Type => "number",
@@ -199,6 +434,28 @@ fn view_julia_set_cfg_editor() -> Node<Msg> {
                         //At::Value => {model.c_imag.to_string()},
                     },
                 ],
+                view_field_error(model, validation::C_IMAG),
+            ],
+            div![
+                C!["input_inner"],
+                label![
+                    C!["input_label"],
+                    attrs! { At::For => "julia_exponent"},
+                    "Exponent"
+                ],
+                input![
+                    C!["input"],
+                    id!("julia_exponent"),
+                    attrs! { At::Value => model.editor.value(validation::EXPONENT) },
+                    input_ev(Ev::Input, |value| Msg::InputChanged(validation::EXPONENT, value)),
+                    attrs! {
+                        At::Name => "julia_exponent",
+                        At::Type => "number",
+                        At::Min => "2",
+                        At::Step => "0.1"
+                    },
+                ],
+                view_field_error(model, validation::EXPONENT),
             ],
             button![
                 C!["editor_button"],
@@ -222,13 +479,16 @@ fn view_julia_set_cfg_editor() -> Node<Msg> {
                     input![
                         C!["input"],
                         id!("julia_max_real"),
+                        attrs! { At::Value => model.editor.value(validation::MAX_REAL) },
+                        input_ev(Ev::Input, |value| Msg::InputChanged(validation::MAX_REAL, value)),
                         attrs! {
                             At::Name => "julia_max_real",
                             At::Type => "number",
                             At::Step => "0.01",
                             //At::Value => {model.x_max.to_string()},
                         },
-                    ]
+                    ],
+                    view_field_error(model, validation::MAX_REAL)
                 ],
                 div![
                     C!["input_inner"],
@@ -240,13 +500,16 @@ fn view_julia_set_cfg_editor() -> Node<Msg> {
                     input![
                         C!["input"],
                         id!("julia_min_real"),
+                        attrs! { At::Value => model.editor.value(validation::MIN_REAL) },
+                        input_ev(Ev::Input, |value| Msg::InputChanged(validation::MIN_REAL, value)),
                         attrs! {
                             At::Name => "julia_min_real",
                             At::Type => "number",
                             At::Step => "0.01",
                             //At::Value => {model.x_min.to_string()},
                         },
-                    ]
+                    ],
+                    view_field_error(model, validation::MIN_REAL)
                 ],
             ],
             div![
@@ -261,13 +524,16 @@ fn view_julia_set_cfg_editor() -> Node<Msg> {
                     input![
                         C!["input"],
                         id!("julia_max_imag"),
+                        attrs! { At::Value => model.editor.value(validation::MAX_IMAG) },
+                        input_ev(Ev::Input, |value| Msg::InputChanged(validation::MAX_IMAG, value)),
                         attrs! {
                             At::Name => "julia_max_imag",
                             At::Type => "number",
                             At::Step => "0.01",
                             //At::Value => {model.y_max.to_string()},
                         },
-                    ]
+                    ],
+                    view_field_error(model, validation::MAX_IMAG)
                 ],
                 div![
                     C!["input_inner"],
@@ -279,13 +545,16 @@ fn view_julia_set_cfg_editor() -> Node<Msg> {
                     input![
                         C!["input"],
                         id!("julia_min_imag"),
+                        attrs! { At::Value => model.editor.value(validation::MIN_IMAG) },
+                        input_ev(Ev::Input, |value| Msg::InputChanged(validation::MIN_IMAG, value)),
                         attrs! {
                             At::Name => "julia_min_imag",
                             At::Type => "number",
                             At::Step => "0.01",
                             //At::Value => {model.y_min.to_string()},
                         },
-                    ]
+                    ],
+                    view_field_error(model, validation::MIN_IMAG)
                 ],
             ],
             div![
@@ -325,9 +594,9 @@ fn view_julia_set_cfg_editor() -> Node<Msg> {
 }
 
 #[allow(clippy::too_many_lines)]
-fn view_mandelbrot_cfg_editor() -> Node<Msg> {
+fn view_mandelbrot_cfg_editor(model: &Model) -> Node<Msg> {
     div![
-        C!["edit_cntr_hidden"],
+        C![editor_class(model, FractalType::Mandelbrot)],
         id!("mandelbrot_edit_cntr"),
 
         div![
@@ -347,6 +616,8 @@ fn view_mandelbrot_cfg_editor() -> Node<Msg> {
                 input![
                     C!["input"],
                     id!("mandelbrot_iterations"),
+                    attrs! { At::Value => model.editor.value(validation::ITERATIONS) },
+                    input_ev(Ev::Input, |value| Msg::InputChanged(validation::ITERATIONS, value)),
                     attrs! {
                         At::Name => "mandelbrot_iterations",
                         At::Type => "number",
@@ -355,6 +626,28 @@ fn view_mandelbrot_cfg_editor() -> Node<Msg> {
                         // At::Value => {model.max_iterations.to_string()},
                     },
                 ],
+                view_field_error(model, validation::ITERATIONS),
+            ],
+            div![
+                C!["input_inner"],
+                label![
+                    C!["input_label"],
+                    attrs! { At::For => "mandelbrot_exponent"},
+                    "Exponent"
+                ],
+                input![
+                    C!["input"],
+                    id!("mandelbrot_exponent"),
+                    attrs! { At::Value => model.editor.value(validation::EXPONENT) },
+                    input_ev(Ev::Input, |value| Msg::InputChanged(validation::EXPONENT, value)),
+                    attrs! {
+                        At::Name => "mandelbrot_exponent",
+                        At::Type => "number",
+                        At::Min => "2",
+                        At::Step => "0.1"
+                    },
+                ],
+                view_field_error(model, validation::EXPONENT),
             ],
             button![
                 C!["editor_button"],
@@ -378,13 +671,16 @@ fn view_mandelbrot_cfg_editor() -> Node<Msg> {
                     input![
                         C!["input"],
                         id!("mandelbrot_max_real"),
+                        attrs! { At::Value => model.editor.value(validation::MAX_REAL) },
+                        input_ev(Ev::Input, |value| Msg::InputChanged(validation::MAX_REAL, value)),
                         attrs! {
                             At::Name => "mandelbrot_max_real",
                             At::Type => "number",
                             At::Step => "0.01",
                             //At::Value => {model.x_max.to_string()},
                         },
-                    ]
+                    ],
+                    view_field_error(model, validation::MAX_REAL)
                 ],
                 div![
                     C!["input_inner"],
@@ -396,13 +692,16 @@ fn view_mandelbrot_cfg_editor() -> Node<Msg> {
                     input![
                         C!["input"],
                         id!("mandelbrot_min_real"),
+                        attrs! { At::Value => model.editor.value(validation::MIN_REAL) },
+                        input_ev(Ev::Input, |value| Msg::InputChanged(validation::MIN_REAL, value)),
                         attrs! {
                             At::Name => "mandelbrot_min_real",
                             At::Type => "number",
                             At::Step => "0.01",
                             //At::Value => {model.x_min.to_string()},
                         },
-                    ]
+                    ],
+                    view_field_error(model, validation::MIN_REAL)
                 ],
             ],
             div![
@@ -417,13 +716,16 @@ fn view_mandelbrot_cfg_editor() -> Node<Msg> {
                     input![
                         C!["input"],
                         id!("mandelbrot_max_imag"),
+                        attrs! { At::Value => model.editor.value(validation::MAX_IMAG) },
+                        input_ev(Ev::Input, |value| Msg::InputChanged(validation::MAX_IMAG, value)),
                         attrs! {
                             At::Name => "mandelbrot_max_imag",
                             At::Type => "number",
                             At::Step => "0.01",
                             //At::Value => {model.y_max.to_string()},
                         },
-                    ]
+                    ],
+                    view_field_error(model, validation::MAX_IMAG)
                 ],
                 div![
                     C!["input_inner"],
@@ -435,13 +737,16 @@ fn view_mandelbrot_cfg_editor() -> Node<Msg> {
                     input![
                         C!["input"],
                         id!("mandelbrot_min_imag"),
+                        attrs! { At::Value => model.editor.value(validation::MIN_IMAG) },
+                        input_ev(Ev::Input, |value| Msg::InputChanged(validation::MIN_IMAG, value)),
                         attrs! {
                             At::Name => "mandelbrot_min_imag",
                             At::Type => "number",
                             At::Step => "0.01",
                             //At::Value => {model.y_min.to_string()},
                         },
-                    ]
+                    ],
+                    view_field_error(model, validation::MIN_IMAG)
                 ],
             ],
             div![
@@ -479,3 +784,615 @@ fn view_mandelbrot_cfg_editor() -> Node<Msg> {
         ]
     ]
 }
+
+#[allow(clippy::too_many_lines)]
+fn view_burning_ship_cfg_editor(model: &Model) -> Node<Msg> {
+    div![
+        C![editor_class(model, FractalType::BurningShip)],
+        id!("burning_ship_edit_cntr"),
+
+        div![
+            C!["input_cntr"],
+            p![ C!["hint_text"],
+                "Hint: You can select a rectangle in the draw area to import the coordiates into the editor."],
+        ],
+        div![
+            C!["input_cntr"],
+            div![
+                C!["input_inner"],
+                label![
+                    C!["input_label"],
+                    attrs! { At::For => "burning_ship_iterations"},
+                    "Iterations"
+                ],
+                input![
+                    C!["input"],
+                    id!("burning_ship_iterations"),
+                    attrs! { At::Value => model.editor.value(validation::ITERATIONS) },
+                    input_ev(Ev::Input, |value| Msg::InputChanged(validation::ITERATIONS, value)),
+                    attrs! {
+                        At::Name => "burning_ship_iterations",
+                        At::Type => "number",
+                        At::Min =>"100",
+                        At::Max =>"1000",
+                    },
+                ],
+                view_field_error(model, validation::ITERATIONS),
+            ],
+            button![
+                C!["editor_button"],
+                id!("burning_ship_reset_params"),
+                ev(Ev::Click, |_| Msg::ResetParams),
+                "Reset to Default",
+            ]
+        ],
+        div![
+            C!["input_cntr"],
+            div![
+                C!["area_cntr"],
+                div![
+                    C!["input_inner"],
+                    label![
+                        C!["input_label"],
+                        attrs! { At::For => "burning_ship_max_real"},
+                        "Max. Real"
+                    ],
+                    input![
+                        C!["input"],
+                        id!("burning_ship_max_real"),
+                        attrs! { At::Value => model.editor.value(validation::MAX_REAL) },
+                        input_ev(Ev::Input, |value| Msg::InputChanged(validation::MAX_REAL, value)),
+                        attrs! {
+                            At::Name => "burning_ship_max_real",
+                            At::Type => "number",
+                            At::Step => "0.01",
+                        },
+                    ],
+                    view_field_error(model, validation::MAX_REAL)
+                ],
+                div![
+                    C!["input_inner"],
+                    label![
+                        C!["input_label"],
+                        attrs! { At::For => "burning_ship_min_real"},
+                        "Min. Real"
+                    ],
+                    input![
+                        C!["input"],
+                        id!("burning_ship_min_real"),
+                        attrs! { At::Value => model.editor.value(validation::MIN_REAL) },
+                        input_ev(Ev::Input, |value| Msg::InputChanged(validation::MIN_REAL, value)),
+                        attrs! {
+                            At::Name => "burning_ship_min_real",
+                            At::Type => "number",
+                            At::Step => "0.01",
+                        },
+                    ],
+                    view_field_error(model, validation::MIN_REAL)
+                ],
+            ],
+            div![
+                C!["area_cntr"],
+                div![
+                    C!["input_inner"],
+                    label![
+                        C!["input_label"],
+                        attrs! { At::For => "burning_ship_max_imag"},
+                        "Max. Imag."
+                    ],
+                    input![
+                        C!["input"],
+                        id!("burning_ship_max_imag"),
+                        attrs! { At::Value => model.editor.value(validation::MAX_IMAG) },
+                        input_ev(Ev::Input, |value| Msg::InputChanged(validation::MAX_IMAG, value)),
+                        attrs! {
+                            At::Name => "burning_ship_max_imag",
+                            At::Type => "number",
+                            At::Step => "0.01",
+                        },
+                    ],
+                    view_field_error(model, validation::MAX_IMAG)
+                ],
+                div![
+                    C!["input_inner"],
+                    label![
+                        C!["input_label"],
+                        attrs! { At::For => "burning_ship_min_imag"},
+                        "Min. Imag."
+                    ],
+                    input![
+                        C!["input"],
+                        id!("burning_ship_min_imag"),
+                        attrs! { At::Value => model.editor.value(validation::MIN_IMAG) },
+                        input_ev(Ev::Input, |value| Msg::InputChanged(validation::MIN_IMAG, value)),
+                        attrs! {
+                            At::Name => "burning_ship_min_imag",
+                            At::Type => "number",
+                            At::Step => "0.01",
+                        },
+                    ],
+                    view_field_error(model, validation::MIN_IMAG)
+                ],
+            ],
+            div![
+                C!["area_cntr"],
+                button![
+                    C!["editor_button"],
+                    id!("burning_ship_reset_area"),
+                    ev(Ev::Click, |_| Msg::ResetArea),
+                    "Reset to Default",
+                ],
+                button![
+                    C!["editor_button"],
+                    id!("burning_ship_zoom_out"),
+                    ev(Ev::Click, |_| Msg::ZoomOutArea),
+                    "Zoom Out",
+                ]
+            ]
+        ],
+        div![
+            C!["edit_button_cntr"],
+            button![
+                C!["editor_button"],
+                id!("burning_ship_save"),
+                ev(Ev::Click, |_| Msg::SaveEdit),
+                "Save"
+            ],
+            button![
+                C!["editor_button"],
+                id!("burning_ship_cancel"),
+                ev(Ev::Click, |_| Msg::CancelEdit),
+                "Cancel"
+            ]
+        ]
+    ]
+}
+
+#[allow(clippy::too_many_lines)]
+fn view_newton_cfg_editor(model: &Model) -> Node<Msg> {
+    div![
+        C![editor_class(model, FractalType::Newton)],
+        id!("newton_edit_cntr"),
+
+        div![
+            C!["input_cntr"],
+            p![ C!["hint_text"],
+                "Hint: You can select a rectangle in the draw area to import the coordiates into the editor."],
+        ],
+        div![
+            C!["input_cntr"],
+            div![
+                C!["input_inner"],
+                label![
+                    C!["input_label"],
+                    attrs! { At::For => "newton_iterations"},
+                    "Iterations"
+                ],
+                input![
+                    C!["input"],
+                    id!("newton_iterations"),
+                    attrs! { At::Value => model.editor.value(validation::ITERATIONS) },
+                    input_ev(Ev::Input, |value| Msg::InputChanged(validation::ITERATIONS, value)),
+                    attrs! {
+                        At::Name => "newton_iterations",
+                        At::Type => "number",
+                        At::Min =>"10",
+                        At::Max =>"1000",
+                    },
+                ],
+                view_field_error(model, validation::ITERATIONS),
+            ],
+            div![
+                C!["input_inner"],
+                label![
+                    C!["input_label"],
+                    attrs! { At::For => "newton_coefficients"},
+                    "Coefficients"
+                ],
+                input![
+                    C!["input"],
+                    id!("newton_coefficients"),
+                    attrs! { At::Value => model.editor.value(validation::COEFFICIENTS) },
+                    input_ev(Ev::Input, |value| Msg::InputChanged(validation::COEFFICIENTS, value)),
+                    attrs! {
+                        At::Name => "newton_coefficients",
+                        At::Type => "text",
+                        At::Title => "Polynomial coefficients, highest degree first, e.g. 1, 0, 0, -1 for z^3 - 1",
+                    },
+                ],
+                view_field_error(model, validation::COEFFICIENTS),
+            ],
+            button![
+                C!["editor_button"],
+                id!("newton_reset_params"),
+                ev(Ev::Click, |_| Msg::ResetParams),
+                "Reset to Default",
+            ]
+        ],
+        div![
+            C!["input_cntr"],
+            div![
+                C!["area_cntr"],
+                div![
+                    C!["input_inner"],
+                    label![
+                        C!["input_label"],
+                        attrs! { At::For => "newton_max_real"},
+                        "Max. Real"
+                    ],
+                    input![
+                        C!["input"],
+                        id!("newton_max_real"),
+                        attrs! { At::Value => model.editor.value(validation::MAX_REAL) },
+                        input_ev(Ev::Input, |value| Msg::InputChanged(validation::MAX_REAL, value)),
+                        attrs! {
+                            At::Name => "newton_max_real",
+                            At::Type => "number",
+                            At::Step => "0.01",
+                        },
+                    ],
+                    view_field_error(model, validation::MAX_REAL)
+                ],
+                div![
+                    C!["input_inner"],
+                    label![
+                        C!["input_label"],
+                        attrs! { At::For => "newton_min_real"},
+                        "Min. Real"
+                    ],
+                    input![
+                        C!["input"],
+                        id!("newton_min_real"),
+                        attrs! { At::Value => model.editor.value(validation::MIN_REAL) },
+                        input_ev(Ev::Input, |value| Msg::InputChanged(validation::MIN_REAL, value)),
+                        attrs! {
+                            At::Name => "newton_min_real",
+                            At::Type => "number",
+                            At::Step => "0.01",
+                        },
+                    ],
+                    view_field_error(model, validation::MIN_REAL)
+                ],
+            ],
+            div![
+                C!["area_cntr"],
+                div![
+                    C!["input_inner"],
+                    label![
+                        C!["input_label"],
+                        attrs! { At::For => "newton_max_imag"},
+                        "Max. Imag."
+                    ],
+                    input![
+                        C!["input"],
+                        id!("newton_max_imag"),
+                        attrs! { At::Value => model.editor.value(validation::MAX_IMAG) },
+                        input_ev(Ev::Input, |value| Msg::InputChanged(validation::MAX_IMAG, value)),
+                        attrs! {
+                            At::Name => "newton_max_imag",
+                            At::Type => "number",
+                            At::Step => "0.01",
+                        },
+                    ],
+                    view_field_error(model, validation::MAX_IMAG)
+                ],
+                div![
+                    C!["input_inner"],
+                    label![
+                        C!["input_label"],
+                        attrs! { At::For => "newton_min_imag"},
+                        "Min. Imag."
+                    ],
+                    input![
+                        C!["input"],
+                        id!("newton_min_imag"),
+                        attrs! { At::Value => model.editor.value(validation::MIN_IMAG) },
+                        input_ev(Ev::Input, |value| Msg::InputChanged(validation::MIN_IMAG, value)),
+                        attrs! {
+                            At::Name => "newton_min_imag",
+                            At::Type => "number",
+                            At::Step => "0.01",
+                        },
+                    ],
+                    view_field_error(model, validation::MIN_IMAG)
+                ],
+            ],
+            div![
+                C!["area_cntr"],
+                button![
+                    C!["editor_button"],
+                    id!("newton_reset_area"),
+                    ev(Ev::Click, |_| Msg::ResetArea),
+                    "Reset to Default",
+                ],
+                button![
+                    C!["editor_button"],
+                    id!("newton_zoom_out"),
+                    ev(Ev::Click, |_| Msg::ZoomOutArea),
+                    "Zoom Out",
+                ]
+            ]
+        ],
+        div![
+            C!["edit_button_cntr"],
+            button![
+                C!["editor_button"],
+                id!("newton_save"),
+                ev(Ev::Click, |_| Msg::SaveEdit),
+                "Save"
+            ],
+            button![
+                C!["editor_button"],
+                id!("newton_cancel"),
+                ev(Ev::Click, |_| Msg::CancelEdit),
+                "Cancel"
+            ]
+        ]
+    ]
+}
+
+// only the editor of the active type is shown while editing
+// state of the checkbox which fired a change event
+fn is_checked(event: &web_sys::Event) -> bool {
+    event
+        .target()
+        .and_then(|target| target.dyn_into::<HtmlInputElement>().ok())
+        .is_some_and(|input| input.checked())
+}
+
+fn editor_class(model: &Model, fractal_type: FractalType) -> &'static str {
+    if model.edit_mode && model.config.active_config == fractal_type {
+        "edit_cntr_visible"
+    } else {
+        "edit_cntr_hidden"
+    }
+}
+
+fn view_field_error(model: &Model, field: &str) -> Node<Msg> {
+    model
+        .editor
+        .error(field)
+        .map_or_else(|| empty![], |message| span![C!["input_error"], message])
+}
+
+fn view_bookmarks(model: &Model) -> Node<Msg> {
+    if !model.view_bookmarks {
+        return empty![];
+    }
+    div![
+        C!["edit_cntr_visible"],
+        id!("bookmarks_cntr"),
+        div![
+            C!["input_cntr"],
+            div![
+                C!["input_inner"],
+                label![
+                    C!["input_label"],
+                    attrs! { At::For => "bookmark_name"},
+                    "Name"
+                ],
+                input![
+                    C!["input"],
+                    id!("bookmark_name"),
+                    attrs! {
+                        At::Type => "text",
+                        At::Value => model.bookmark_name,
+                        At::Placeholder => "Bookmark name",
+                    },
+                    input_ev(Ev::Input, Msg::BookmarkName),
+                ],
+            ],
+            button![
+                C!["editor_button"],
+                id!("bookmark_add"),
+                ev(Ev::Click, |_| Msg::AddBookmark),
+                "Save Bookmark"
+            ],
+        ],
+        model
+            .bookmarks
+            .entries()
+            .iter()
+            .enumerate()
+            .map(|(idx, bookmark)| view_bookmark(idx, bookmark, model.edit_mode)),
+        div![
+            C!["edit_button_cntr"],
+            button![
+                C!["editor_button"],
+                id!("bookmarks_export"),
+                ev(Ev::Click, |_| Msg::ExportBookmarks),
+                IF!(model.bookmarks.entries().is_empty() => attrs!{At::Disabled => "true"}),
+                "Export"
+            ],
+            label![
+                C!["input_label"],
+                attrs! { At::For => "bookmarks_import"},
+                "Import"
+            ],
+            input![
+                C!["input"],
+                id!("bookmarks_import"),
+                attrs! {
+                    At::Type => "file",
+                    At::Accept => ".json,application/json",
+                },
+                ev(Ev::Change, |event| {
+                    let input = event
+                        .target()
+                        .and_then(|target| target.dyn_into::<HtmlInputElement>().ok());
+                    let file = input
+                        .as_ref()
+                        .and_then(HtmlInputElement::files)
+                        .and_then(|files| files.get(0));
+                    // allow importing the same file again
+                    if let Some(input) = input {
+                        input.set_value("");
+                    }
+                    Msg::ImportBookmarks(file)
+                }),
+            ],
+        ],
+    ]
+}
+
+fn view_bookmark(idx: usize, bookmark: &Bookmark, edit_mode: bool) -> Node<Msg> {
+    let name_id = format!("bookmark_name_{idx}");
+    div![
+        C!["input_cntr"],
+        IF!(!bookmark.thumbnail.is_empty() =>
+            img![
+                C!["bookmark_thumbnail"],
+                attrs! {
+                    At::Src => bookmark.thumbnail,
+                    At::Alt => bookmark.name,
+                    At::Title => "Restore",
+                },
+                IF!(!edit_mode => ev(Ev::Click, move |_| Msg::RestoreBookmark(idx))),
+            ]
+        ),
+        input![
+            C!["input"],
+            id!(name_id.as_str()),
+            attrs! {
+                At::Type => "text",
+                At::Value => bookmark.name,
+            },
+            input_ev(Ev::Change, move |value| Msg::RenameBookmark(idx, value)),
+        ],
+        button![
+            C!["editor_button"],
+            ev(Ev::Click, move |_| Msg::RestoreBookmark(idx)),
+            IF!(edit_mode => attrs!{At::Disabled => "true"}),
+            "Restore"
+        ],
+        button![
+            C!["editor_button"],
+            ev(Ev::Click, move |_| Msg::DeleteBookmark(idx)),
+            "Delete"
+        ],
+    ]
+}
+
+#[allow(clippy::option_if_let_else)]
+fn view_palette_editor(model: &Model) -> Node<Msg> {
+    if let Some(palette) = model.palette_edit.as_ref() {
+        div![
+            C!["edit_cntr_visible"],
+            id!("palette_edit_cntr"),
+            div![
+                C!["input_cntr"],
+                div![
+                    C!["input_inner"],
+                    label![
+                        C!["input_label"],
+                        attrs! { At::For => "palette_interpolation"},
+                        "Interpolation"
+                    ],
+                    select![
+                        C!["input"],
+                        id!("palette_interpolation"),
+                        attrs! {At::Name => "palette_interpolation" },
+                        option![
+                            attrs! {At::Value => "rgb" },
+                            IF!(palette.interpolation == Interpolation::Rgb => attrs!{At::Selected => "true"}),
+                            "RGB"
+                        ],
+                        option![
+                            attrs! {At::Value => "hsl" },
+                            IF!(palette.interpolation == Interpolation::Hsl => attrs!{At::Selected => "true"}),
+                            "HSL"
+                        ],
+                        input_ev(Ev::Change, Msg::PaletteInterpolation),
+                    ],
+                ],
+                button![
+                    C!["editor_button"],
+                    id!("palette_add_stop"),
+                    ev(Ev::Click, |_| Msg::AddPaletteStop),
+                    "Add Stop"
+                ],
+                button![
+                    C!["editor_button"],
+                    id!("palette_reset"),
+                    ev(Ev::Click, |_| Msg::ResetPalette),
+                    "Reset to Default"
+                ],
+            ],
+            palette
+                .stops
+                .iter()
+                .enumerate()
+                .map(|(idx, stop)| view_palette_stop(idx, *stop, palette.stops.len() > MIN_STOPS)),
+            div![
+                C!["edit_button_cntr"],
+                button![
+                    C!["editor_button"],
+                    id!("palette_save"),
+                    ev(Ev::Click, |_| Msg::SavePalette),
+                    "Save"
+                ],
+                button![
+                    C!["editor_button"],
+                    id!("palette_cancel"),
+                    ev(Ev::Click, |_| Msg::CancelPalette),
+                    "Cancel"
+                ]
+            ]
+        ]
+    } else {
+        div![C!["edit_cntr_hidden"], id!("palette_edit_cntr")]
+    }
+}
+
+fn view_palette_stop(idx: usize, stop: ColorStop, removable: bool) -> Node<Msg> {
+    let color_id = format!("palette_color_{idx}");
+    let position_id = format!("palette_position_{idx}");
+    div![
+        C!["input_cntr"],
+        div![
+            C!["input_inner"],
+            label![
+                C!["input_label"],
+                attrs! { At::For => color_id.as_str()},
+                "Color"
+            ],
+            input![
+                C!["input"],
+                id!(color_id.as_str()),
+                attrs! {
+                    At::Name => color_id,
+                    At::Type => "color",
+                    At::Value => rgb_to_html(stop.color).to_lowercase(),
+                },
+                input_ev(Ev::Input, move |value| Msg::PaletteStopColor(idx, value)),
+            ],
+        ],
+        div![
+            C!["input_inner"],
+            label![
+                C!["input_label"],
+                attrs! { At::For => position_id.as_str()},
+                "Position"
+            ],
+            input![
+                C!["input"],
+                id!(position_id.as_str()),
+                attrs! {
+                    At::Name => position_id,
+                    At::Type => "number",
+                    At::Min => "0",
+                    At::Max => "1",
+                    At::Step => "0.01",
+                    At::Value => stop.position.to_string(),
+                },
+                input_ev(Ev::Input, move |value| Msg::PaletteStopPosition(idx, value)),
+            ],
+        ],
+        button![
+            C!["editor_button"],
+            ev(Ev::Click, move |_| Msg::RemovePaletteStop(idx)),
+            IF!(!removable =>  attrs!{At::Disabled => "true" } ),
+            "Remove"
+        ],
+    ]
+}
@@ -0,0 +1,343 @@
+use seed::prelude::web_sys::{ErrorEvent, MessageEvent, Worker, WorkerOptions, WorkerType};
+use seed::{
+    log,
+    prelude::{js_sys, wasm_bindgen, web_sys, Closure, JsCast, JsValue, Orders},
+};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+
+use super::{
+    clock::{Clock, PerformanceClock},
+    fractal::{Fractal, IterationBuffer, KnownPoints, Points, Tile},
+    render::create_fractal,
+    scheduler::Scheduler,
+    stats::Stats,
+    Config, Msg,
+};
+
+// loads the wasm module in the worker and passes it every message, relative to the
+// page as the app may be served from a sub directory, see mod_index in Makefile.toml
+const WORKER_SCRIPT: &str = "worker.js";
+// used if the browser does not tell the number of cores
+const DEFAULT_WORKERS: usize = 4;
+const MAX_WORKERS: usize = 16;
+
+// settings of a render, sent to a worker once before its first tile of the render
+#[derive(Serialize, Deserialize)]
+struct Render {
+    generation: u32,
+    // size of the whole image
+    width: u32,
+    height: u32,
+    config: Config,
+}
+
+// a tile of the render with the same generation to calculate
+#[derive(Serialize, Deserialize)]
+struct Job {
+    generation: u32,
+    worker: usize,
+    tile: Tile,
+    // points of the tile calculated by a coarser pass or copied from a panned view,
+    // (x, y, iterations, smooth iterations, root)
+    known: Vec<(u32, u32, u32, f32, u8)>,
+}
+
+// sent to a worker as json
+#[derive(Serialize, Deserialize)]
+enum Message {
+    Render(Box<Render>),
+    Job(Job),
+}
+
+// fractal of the render a worker calculates tiles for, built once per render
+struct WorkerRender {
+    generation: u32,
+    view_stats: bool,
+    fractal: Box<dyn Fractal>,
+}
+
+thread_local! {
+    static RENDER: RefCell<Option<WorkerRender>> = const { RefCell::new(None) };
+}
+
+// sent back by a worker followed by the values, smooth iterations and roots of the tile
+#[derive(Serialize, Deserialize)]
+struct Header {
+    generation: u32,
+    worker: usize,
+    tile: Tile,
+    stats: Option<Stats>,
+}
+
+/// Web Workers calculating the tiles of an image in parallel, the main thread only draws
+/// the results. Each worker gets one tile of the `Scheduler` at a time, the next one is sent
+/// when its result arrives.
+pub struct WorkerPool {
+    workers: Vec<Worker>,
+    // generation of the render a worker is busy with
+    busy: Vec<Option<u32>>,
+    // generation of the render whose settings a worker has
+    started: Vec<Option<u32>>,
+    // results of cancelled renders are dropped
+    generation: u32,
+    // settings of the current render as message to the workers
+    render: Option<String>,
+    _on_message: Closure<dyn FnMut(MessageEvent)>,
+    _on_error: Closure<dyn FnMut(ErrorEvent)>,
+}
+
+impl WorkerPool {
+    /// Start one worker per core. Returns None if the browser does not support module workers,
+    /// rendering then stays on the main thread.
+    pub fn new(orders: &impl Orders<Msg>) -> Option<Self> {
+        let window = web_sys::window()?;
+        if !js_sys::Reflect::has(&window, &JsValue::from_str("Worker")).unwrap_or(false) {
+            log!("web workers are not available");
+            return None;
+        }
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let cores = window.navigator().hardware_concurrency() as usize;
+        let count = if cores == 0 {
+            DEFAULT_WORKERS
+        } else {
+            usize::min(cores, MAX_WORKERS)
+        };
+
+        let sender = orders.msg_sender();
+        let on_message = Closure::wrap(Box::new(move |event: MessageEvent| {
+            sender(Some(Msg::TileDone(event.data())));
+        }) as Box<dyn FnMut(MessageEvent)>);
+        let sender = orders.msg_sender();
+        let on_error = Closure::wrap(Box::new(move |event: ErrorEvent| {
+            sender(Some(Msg::WorkerFailed(event.message())));
+        }) as Box<dyn FnMut(ErrorEvent)>);
+
+        let mut options = WorkerOptions::new();
+        options.type_(WorkerType::Module);
+        let mut workers = Vec::with_capacity(count);
+        for _idx in 0..count {
+            match Worker::new_with_options(WORKER_SCRIPT, &options) {
+                Ok(worker) => {
+                    worker.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+                    worker.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+                    workers.push(worker);
+                }
+                Err(err) => {
+                    log!(format!("failed to start web worker: {:?}", err));
+                    workers.iter().for_each(Worker::terminate);
+                    return None;
+                }
+            }
+        }
+        log!(format!("started {} web workers", count));
+
+        Some(Self {
+            busy: vec![None; workers.len()],
+            started: vec![None; workers.len()],
+            workers,
+            generation: 0,
+            render: None,
+            _on_message: on_message,
+            _on_error: on_error,
+        })
+    }
+
+    /// Start calculating an image of `config`, a running render is cancelled.
+    /// Tiles are requested from the scheduler passed to `dispatch`.
+    pub fn start(&mut self, config: &Config, width: u32, height: u32) {
+        self.cancel();
+        let render = Message::Render(Box::new(Render {
+            generation: self.generation,
+            width,
+            height,
+            config: config.clone(),
+        }));
+        self.render = Some(serde_json::to_string(&render).expect("failed to serialize render"));
+    }
+
+    /// Ignore the results of the tiles in progress.
+    pub fn cancel(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+        self.render = None;
+    }
+
+    /// True if a worker calculates a tile of the current render.
+    pub fn is_busy(&self) -> bool {
+        self.busy.contains(&Some(self.generation))
+    }
+
+    /// Handle a message of a worker. Returns the results and statistics of a tile, None for
+    /// results of cancelled renders. An error means the worker failed.
+    pub fn receive(&mut self, data: &JsValue) -> Result<Option<(Points, Option<Stats>)>, String> {
+        let (header, points) = parse_result(data)?;
+        if let Some(busy) = self.busy.get_mut(header.worker) {
+            *busy = None;
+        }
+        Ok(if header.generation == self.generation {
+            Some((points, header.stats))
+        } else {
+            None
+        })
+    }
+
+    /// Send the next tiles of `scheduler` to the idle workers, with their points already
+    /// known in `buffer`. A worker gets the settings of the render before its first tile.
+    /// A tile that could not be sent is handed out again and an error is returned, the workers
+    /// are not usable then.
+    pub fn dispatch(
+        &mut self,
+        scheduler: &mut Scheduler,
+        buffer: &IterationBuffer,
+    ) -> Result<(), String> {
+        let Some(render) = self.render.as_ref() else {
+            return Ok(());
+        };
+        for (worker, busy) in self.busy.iter_mut().enumerate() {
+            if busy.is_some() {
+                continue;
+            }
+            let Some(tile) = scheduler.next() else {
+                break;
+            };
+            let job = Message::Job(Job {
+                generation: self.generation,
+                worker,
+                tile,
+                known: (0..tile.num_points())
+                    .filter_map(|idx| {
+                        let (x, y) = tile.position(idx);
+                        buffer
+                            .known(x, y)
+                            .map(|(value, smooth, root)| (x, y, value, smooth, root))
+                    })
+                    .collect(),
+            });
+            let message = serde_json::to_string(&job).expect("failed to serialize job");
+            let mut res = if self.started[worker] == Some(self.generation) {
+                Ok(())
+            } else {
+                self.workers[worker].post_message(&JsValue::from_str(render))
+            };
+            if res.is_ok() {
+                self.started[worker] = Some(self.generation);
+                res = self.workers[worker].post_message(&JsValue::from_str(&message));
+            }
+            if let Err(err) = res {
+                scheduler.requeue(tile);
+                return Err(format!("failed to post job to worker {worker}: {err:?}"));
+            }
+            *busy = Some(self.generation);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for WorkerPool {
+    fn drop(&mut self) {
+        self.workers.iter().for_each(Worker::terminate);
+    }
+}
+
+fn parse_result(data: &JsValue) -> Result<(Header, Points), String> {
+    if let Some(err) = data.as_string() {
+        return Err(err);
+    }
+    let array = data
+        .dyn_ref::<js_sys::Array>()
+        .ok_or_else(|| "unexpected worker message".to_string())?;
+    let header: Header = serde_json::from_str(
+        &array
+            .get(0)
+            .as_string()
+            .ok_or_else(|| "missing result header".to_string())?,
+    )
+    .map_err(|err| format!("invalid result header: {err}"))?;
+    let mut points = Points::new(header.tile);
+    points.values = array
+        .get(1)
+        .dyn_into::<js_sys::Uint32Array>()
+        .map_err(|_| "missing values".to_string())?
+        .to_vec();
+    points.smooth = array
+        .get(2)
+        .dyn_into::<js_sys::Float32Array>()
+        .map_err(|_| "missing smooth iterations".to_string())?
+        .to_vec();
+    points.roots = array
+        .get(3)
+        .dyn_into::<js_sys::Uint8Array>()
+        .map_err(|_| "missing roots".to_string())?
+        .to_vec();
+    let len = header.tile.num_points();
+    if points.values.len() != len || points.smooth.len() != len || points.roots.len() != len {
+        return Err(format!("expected {len} points in tile result"));
+    }
+    Ok((header, points))
+}
+
+/// Entry point of the workers: keep the settings of a render or calculate a tile of it, given
+/// as json `message`. Returns the result header, values, smooth iterations and roots of a tile.
+#[wasm_bindgen]
+pub fn handle_message(message: &str) -> Result<Option<js_sys::Array>, JsValue> {
+    let message: Message =
+        serde_json::from_str(message).map_err(|err| JsValue::from_str(&err.to_string()))?;
+    match message {
+        Message::Render(render) => {
+            let fractal = create_fractal(
+                &render.config,
+                render.width,
+                render.height,
+                Box::new(PerformanceClock),
+            );
+            RENDER.with(|current| {
+                *current.borrow_mut() = Some(WorkerRender {
+                    generation: render.generation,
+                    view_stats: render.config.view_stats,
+                    fractal,
+                });
+            });
+            Ok(None)
+        }
+        Message::Job(job) => RENDER.with(|render| match render.borrow_mut().as_mut() {
+            Some(render) if render.generation == job.generation => {
+                render_tile(render, &job).map(Some)
+            }
+            _ => Err(JsValue::from_str(&format!(
+                "missing settings of render {}",
+                job.generation
+            ))),
+        }),
+    }
+}
+
+fn render_tile(render: &mut WorkerRender, job: &Job) -> Result<js_sys::Array, JsValue> {
+    // the known points of the previous tile are replaced, they and the coarse samples the
+    // fractal caches are kept for this tile instead of the whole image
+    let mut known = KnownPoints::for_tile(&job.tile);
+    for &(x, y, value, smooth, root) in &job.known {
+        known.insert(x, y, (value, smooth, root));
+    }
+    render.fractal.set_known_points(known);
+    let mut stats = if render.view_stats {
+        Some(Stats::new(PerformanceClock.now()))
+    } else {
+        None
+    };
+    let points = render.fractal.calculate(&job.tile, stats.as_mut());
+
+    let header = Header {
+        generation: job.generation,
+        worker: job.worker,
+        tile: job.tile,
+        stats,
+    };
+    let result = js_sys::Array::new();
+    result.push(&JsValue::from_str(
+        &serde_json::to_string(&header).map_err(|err| JsValue::from_str(&err.to_string()))?,
+    ));
+    result.push(&js_sys::Uint32Array::from(points.values.as_slice()));
+    result.push(&js_sys::Float32Array::from(points.smooth.as_slice()));
+    result.push(&js_sys::Uint8Array::from(points.roots.as_slice()));
+    Ok(result)
+}
@@ -0,0 +1,19 @@
+// Web Worker calculating tiles of the image, see src/workers.rs
+// relative to this script, the app may be served from a sub directory
+import init, { handle_message } from './pkg/package.js';
+
+const ready = init(new URL('./pkg/package_bg.wasm', import.meta.url));
+
+self.onmessage = async (event) => {
+    await ready;
+    try {
+        // only tiles are answered, the settings of a render are kept for its tiles
+        const result = handle_message(event.data);
+        if (result) {
+            self.postMessage(result, [result[1].buffer, result[2].buffer, result[3].buffer]);
+        }
+    } catch (err) {
+        // a string message makes the main thread fall back to rendering on its own
+        self.postMessage(String(err));
+    }
+};